@@ -0,0 +1,178 @@
+//! Memory budget for mesh vertex and index buffers.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Whether a mesh's buffers are on the GPU when it is requested.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Residency {
+    Resident,
+    /// New or previously evicted; the caller must upload its buffers.
+    NeedsUpload,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BudgetStats {
+    pub used: u64,
+    pub budget: u64,
+    pub resident: usize,
+    pub evicted: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+    bytes: u64,
+    last_used: u64,
+    resident: bool,
+}
+
+/// Tracks geometry memory and evicts the least recently used meshes once
+/// their combined size exceeds the budget.
+///
+/// Meshes used within the last `frames_in_flight` frames may still be read by
+/// the GPU and are never evicted, even if that leaves usage over budget.
+#[derive(Debug)]
+pub struct GeometryBudget<K> {
+    budget: u64,
+    frames_in_flight: u64,
+    used: u64,
+    entries: HashMap<K, Entry>,
+}
+
+impl<K: Clone + Eq + Hash> GeometryBudget<K> {
+    pub fn new(budget: u64, frames_in_flight: u64) -> Self {
+        GeometryBudget {
+            budget,
+            frames_in_flight: frames_in_flight.max(1),
+            used: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Records that `key`, whose buffers take `bytes`, is drawn in `frame`.
+    ///
+    /// A resident mesh re-uploaded at a different size keeps its residency
+    /// and usage is adjusted by the difference.
+    pub fn use_mesh(&mut self, key: &K, bytes: u64, frame: u64) -> Residency {
+        let entry = self.entries.entry(key.clone()).or_insert(Entry {
+            bytes,
+            last_used: frame,
+            resident: false,
+        });
+        entry.last_used = frame;
+        if entry.resident {
+            self.used = self.used - entry.bytes + bytes;
+            entry.bytes = bytes;
+            return Residency::Resident;
+        }
+        entry.bytes = bytes;
+        entry.resident = true;
+        self.used += bytes;
+        Residency::NeedsUpload
+    }
+
+    /// Forgets a mesh entirely, returning whether its buffers were resident.
+    pub fn remove(&mut self, key: &K) -> bool {
+        match self.entries.remove(key) {
+            Some(entry) if entry.resident => {
+                self.used -= entry.bytes;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Evicts least recently used meshes until usage fits the budget and
+    /// returns their keys so the caller can free the buffers.
+    pub fn evict(&mut self, frame: u64) -> Vec<K> {
+        if self.used <= self.budget {
+            return Vec::new();
+        }
+        let in_flight_since = (frame + 1).saturating_sub(self.frames_in_flight);
+        let mut candidates: Vec<(K, u64)> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.resident && entry.last_used < in_flight_since)
+            .map(|(key, entry)| (key.clone(), entry.last_used))
+            .collect();
+        candidates.sort_by_key(|&(_, last_used)| last_used);
+
+        let mut evicted = Vec::new();
+        for (key, _) in candidates {
+            if self.used <= self.budget {
+                break;
+            }
+            if let Some(entry) = self.entries.get_mut(&key) {
+                entry.resident = false;
+                self.used -= entry.bytes;
+            }
+            evicted.push(key);
+        }
+        evicted
+    }
+
+    pub fn stats(&self) -> BudgetStats {
+        let resident = self.entries.values().filter(|e| e.resident).count();
+        BudgetStats {
+            used: self.used,
+            budget: self.budget,
+            resident,
+            evicted: self.entries.len() - resident,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resizing_a_resident_mesh_updates_usage() {
+        let mut budget = GeometryBudget::new(1000, 2);
+        assert_eq!(budget.use_mesh(&"a", 100, 0), Residency::NeedsUpload);
+        assert_eq!(budget.use_mesh(&"a", 300, 1), Residency::Resident);
+        assert_eq!(budget.stats().used, 300);
+        assert_eq!(budget.use_mesh(&"a", 50, 2), Residency::Resident);
+        assert_eq!(budget.stats().used, 50);
+        assert!(budget.remove(&"a"));
+        assert_eq!(budget.stats().used, 0);
+    }
+
+    #[test]
+    fn evicts_least_recently_used_first() {
+        let mut budget = GeometryBudget::new(250, 2);
+        for (frame, key) in ["a", "b", "c"].iter().enumerate() {
+            budget.use_mesh(key, 100, frame as u64);
+        }
+        budget.use_mesh(&"a", 100, 3);
+        budget.use_mesh(&"c", 100, 5);
+
+        assert_eq!(budget.evict(5), vec!["b"]);
+        assert_eq!(
+            budget.stats(),
+            BudgetStats {
+                used: 200,
+                budget: 250,
+                resident: 2,
+                evicted: 1,
+            }
+        );
+        assert!(budget.evict(5).is_empty());
+
+        assert_eq!(budget.use_mesh(&"b", 100, 6), Residency::NeedsUpload);
+        assert_eq!(budget.stats().evicted, 0);
+    }
+
+    #[test]
+    fn meshes_in_flight_are_never_evicted() {
+        let mut budget = GeometryBudget::new(100, 3);
+        for (frame, key) in ["a", "b", "c"].iter().enumerate() {
+            budget.use_mesh(key, 100, 8 + frame as u64);
+        }
+        assert!(budget.evict(10).is_empty());
+        assert_eq!(budget.stats().used, 300);
+
+        assert_eq!(budget.evict(12), vec!["a", "b"]);
+        assert_eq!((budget.stats().resident, budget.stats().evicted), (1, 2));
+    }
+}
@@ -0,0 +1,392 @@
+//! Camera state and the controllers that drive it.
+
+use crate::input::InputState;
+use crate::math::{Mat4, Vec3};
+use std::f32::consts::{FRAC_PI_2, PI};
+use std::hash::Hash;
+use std::time::Duration;
+
+/// Pitch stays just short of straight up or down so the view basis never
+/// degenerates and flips.
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+/// Position and orientation of a camera, with angles in radians.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Pose {
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl Pose {
+    /// Unit vector the pose looks along; yaw 0 and pitch 0 look down -Z.
+    pub fn forward(&self) -> Vec3 {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        Vec3::new(-sin_yaw * cos_pitch, sin_pitch, -cos_yaw * cos_pitch)
+    }
+
+    /// Unit vector to the right of the pose, kept level with the ground.
+    pub fn right(&self) -> Vec3 {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        Vec3::new(cos_yaw, 0.0, -sin_yaw)
+    }
+}
+
+/// A first-person camera moved with keyboard input and turned with the mouse.
+#[derive(Clone, Copy, Debug)]
+pub struct Camera {
+    pub pose: Pose,
+    /// Vertical field of view in radians.
+    pub fov_y: f32,
+    pub near: f32,
+    pub far: f32,
+    /// Movement speed in units per second.
+    pub speed: f32,
+    /// Radians turned per unit of mouse motion.
+    pub sensitivity: f32,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera {
+            pose: Pose::default(),
+            fov_y: 60f32.to_radians(),
+            near: 0.1,
+            far: 1000.0,
+            speed: 5.0,
+            sensitivity: 0.002,
+        }
+    }
+}
+
+impl Camera {
+    /// Applies one frame of input.
+    ///
+    /// `movement` is the requested direction in camera space (x right,
+    /// y up, z forward), each component in `[-1, 1]`. `mouse_delta` should
+    /// be relative device motion rather than a difference of cursor
+    /// positions, so looking keeps working when the cursor is grabbed or
+    /// reaches the edge of the window.
+    pub fn update(&mut self, movement: Vec3, mouse_delta: (f32, f32), delta: Duration) {
+        self.pose.yaw = (self.pose.yaw - mouse_delta.0 * self.sensitivity).rem_euclid(2.0 * PI);
+        self.pose.pitch =
+            (self.pose.pitch - mouse_delta.1 * self.sensitivity).clamp(-MAX_PITCH, MAX_PITCH);
+
+        let direction = self.pose.right() * movement.x
+            + Vec3::Y * movement.y
+            + self.pose.forward() * movement.z;
+        let direction = if direction.length() > 1.0 {
+            direction.normalize()
+        } else {
+            direction
+        };
+        self.pose.position += direction * self.speed * delta.as_secs_f32();
+    }
+
+    pub fn view_matrix(&self) -> Mat4 {
+        Mat4::look_to(self.pose.position, self.pose.forward(), Vec3::Y)
+    }
+
+    /// Projection for the current window aspect ratio; pass the new ratio
+    /// after every resize.
+    pub fn projection_matrix(&self, aspect: f32) -> Mat4 {
+        Mat4::perspective(self.fov_y, aspect, self.near, self.far)
+    }
+
+    pub fn view_projection(&self, aspect: f32) -> Mat4 {
+        self.projection_matrix(aspect) * self.view_matrix()
+    }
+}
+
+/// Keys that move a first-person camera.
+#[derive(Clone, Copy, Debug)]
+pub struct MovementKeys<K> {
+    pub forward: K,
+    pub back: K,
+    pub left: K,
+    pub right: K,
+    pub up: K,
+    pub down: K,
+}
+
+impl<K: Copy + Eq + Hash> MovementKeys<K> {
+    /// The camera-space movement requested by the held keys.
+    pub fn movement(&self, input: &InputState<K>) -> Vec3 {
+        let axis = |positive: K, negative: K| {
+            input.is_key_down(positive) as i32 as f32 - input.is_key_down(negative) as i32 as f32
+        };
+        Vec3::new(
+            axis(self.right, self.left),
+            axis(self.up, self.down),
+            axis(self.forward, self.back),
+        )
+    }
+}
+
+/// Exponential smoothing of a camera pose toward a target.
+///
+/// `factor` is the fraction of the remaining distance still left after
+/// 1/60 s, so `0.0` snaps straight to the target and values closer to `1.0`
+/// trail further behind. The blend is derived from the frame's delta time,
+/// which keeps the motion identical at any frame rate.
+#[derive(Clone, Copy, Debug)]
+pub struct Smoothing {
+    factor: f32,
+    current: Option<Pose>,
+}
+
+/// Largest accepted smoothing factor; `1.0` would never move at all.
+pub const MAX_SMOOTHING: f32 = 0.999;
+
+impl Smoothing {
+    /// Creates a smoother, clamping `factor` to `0.0..=MAX_SMOOTHING`.
+    pub fn new(factor: f32) -> Self {
+        Smoothing {
+            factor: factor.clamp(0.0, MAX_SMOOTHING),
+            current: None,
+        }
+    }
+
+    pub fn factor(&self) -> f32 {
+        self.factor
+    }
+
+    /// Moves the smoothed pose toward `target` and returns it.
+    pub fn update(&mut self, target: Pose, delta: Duration) -> Pose {
+        let current = match self.current {
+            Some(current) if self.factor > 0.0 => current,
+            _ => {
+                self.current = Some(target);
+                return target;
+            }
+        };
+
+        let t = 1.0 - self.factor.powf(delta.as_secs_f32() * 60.0);
+        let pose = Pose {
+            position: current.position.lerp(target.position, t),
+            yaw: current.yaw + wrap_angle(target.yaw - current.yaw) * t,
+            pitch: current.pitch + (target.pitch - current.pitch) * t,
+        };
+        self.current = Some(pose);
+        pose
+    }
+
+    /// Drops the smoothed state so the next update snaps to its target.
+    pub fn reset(&mut self) {
+        self.current = None;
+    }
+}
+
+impl Default for Smoothing {
+    fn default() -> Self {
+        Smoothing::new(0.0)
+    }
+}
+
+/// Wraps an angle difference into `[-PI, PI)` so yaw blends the short way round.
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
+/// Slow rotation of a model around its vertical axis, for turntable previews.
+#[derive(Clone, Copy, Debug)]
+pub struct Turntable {
+    /// Rotation speed in radians per second.
+    pub speed: f32,
+    enabled: bool,
+    angle: f32,
+}
+
+impl Turntable {
+    pub fn new(speed: f32) -> Self {
+        Turntable {
+            speed,
+            enabled: false,
+            angle: 0.0,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    /// Stops the rotation in response to manual camera input. It resumes from
+    /// the same angle the next time it is toggled on.
+    pub fn interrupt(&mut self) {
+        self.enabled = false;
+    }
+
+    /// Advances the rotation and returns the current angle around +Y.
+    pub fn update(&mut self, delta: Duration) -> f32 {
+        if self.enabled {
+            self.angle = (self.angle + self.speed * delta.as_secs_f32()).rem_euclid(2.0 * PI);
+        }
+        self.angle
+    }
+}
+
+/// Returns the center of the bounds `min..max` and the distance a camera with
+/// vertical field of view `fov_y` and the given aspect ratio must sit from it
+/// to keep the whole box in view.
+///
+/// The narrower of the vertical and horizontal fields of view decides, so
+/// portrait windows pull the camera back instead of cropping the sides.
+pub fn frame_bounds(min: Vec3, max: Vec3, fov_y: f32, aspect: f32) -> (Vec3, f32) {
+    let center = min.lerp(max, 0.5);
+    let radius = (max - min).length() / 2.0;
+    let half_y = fov_y / 2.0;
+    let half_x = (half_y.tan() * aspect).atan();
+    (center, radius / half_y.min(half_x).sin())
+}
+
+/// A decaying shake layered on top of whatever controller drives the camera.
+///
+/// The offset is built from sine waves with randomized phases, so it moves
+/// smoothly rather than jumping between samples, and it fades out with a
+/// quadratic envelope that reaches zero with zero slope, returning the camera
+/// to its base pose without a snap.
+#[derive(Clone, Debug)]
+pub struct Shake {
+    /// Oscillation frequency in hertz.
+    pub frequency: f32,
+    /// Maximum rotation in radians, applied at full position magnitude.
+    pub max_rotation: f32,
+    duration: f32,
+    magnitude: f32,
+    elapsed: f32,
+    phases: [f32; 5],
+    seed: u32,
+}
+
+impl Shake {
+    pub fn new() -> Self {
+        Shake {
+            frequency: 15.0,
+            max_rotation: 0.02,
+            duration: 0.0,
+            magnitude: 0.0,
+            elapsed: 0.0,
+            phases: [0.0; 5],
+            seed: 0x9e37_79b9,
+        }
+    }
+
+    /// Starts a shake lasting `duration` with an initial offset of up to
+    /// `magnitude`. A stronger shake replaces a weaker one in progress.
+    pub fn trigger(&mut self, duration: Duration, magnitude: f32) {
+        if self.is_active() && self.remaining_magnitude() > magnitude {
+            return;
+        }
+        self.duration = duration.as_secs_f32();
+        self.magnitude = magnitude;
+        self.elapsed = 0.0;
+        for i in 0..self.phases.len() {
+            self.phases[i] = self.next_random() * 2.0 * PI;
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.elapsed < self.duration
+    }
+
+    /// Advances the shake and returns `base` with the current offset applied.
+    pub fn update(&mut self, base: Pose, delta: Duration) -> Pose {
+        if !self.is_active() {
+            return base;
+        }
+        self.elapsed = (self.elapsed + delta.as_secs_f32()).min(self.duration);
+
+        let strength = self.remaining_magnitude();
+        let wave = |phase: f32| (self.elapsed * self.frequency * 2.0 * PI + phase).sin();
+        let offset = Vec3::new(
+            wave(self.phases[0]),
+            wave(self.phases[1]),
+            wave(self.phases[2]),
+        ) * strength;
+        let rotation = self.max_rotation * strength / self.magnitude.max(f32::EPSILON);
+        Pose {
+            position: base.position + offset,
+            yaw: base.yaw + wave(self.phases[3]) * rotation,
+            pitch: base.pitch + wave(self.phases[4]) * rotation,
+        }
+    }
+
+    fn remaining_magnitude(&self) -> f32 {
+        if self.duration <= 0.0 {
+            return 0.0;
+        }
+        let remaining = 1.0 - self.elapsed / self.duration;
+        self.magnitude * remaining * remaining
+    }
+
+    /// Xorshift, returning a value in `[0, 1)`.
+    fn next_random(&mut self) -> f32 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        (self.seed >> 8) as f32 / (1u32 << 24) as f32
+    }
+}
+
+impl Default for Shake {
+    fn default() -> Self {
+        Shake::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_bounds_uses_the_narrower_field_of_view() {
+        let (min, max) = (Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+        let fov_y = 60f32.to_radians();
+        let (center, landscape) = frame_bounds(min, max, fov_y, 16.0 / 9.0);
+        assert_eq!(center, Vec3::ZERO);
+        assert!((landscape - 3f32.sqrt() / 0.5).abs() < 1e-4);
+
+        let (_, portrait) = frame_bounds(min, max, fov_y, 0.5);
+        let half_x = ((fov_y / 2.0).tan() * 0.5).atan();
+        assert!((portrait - 3f32.sqrt() / half_x.sin()).abs() < 1e-4);
+        assert!(portrait > landscape);
+    }
+
+    #[test]
+    fn smoothing_is_independent_of_frame_rate() {
+        let start = Pose {
+            position: Vec3::ZERO,
+            yaw: 0.0,
+            pitch: 0.0,
+        };
+        let target = Pose {
+            position: Vec3::new(10.0, -4.0, 2.0),
+            yaw: 1.5,
+            pitch: -0.5,
+        };
+        let step = |steps: u32| {
+            let mut smoothing = Smoothing::new(0.8);
+            smoothing.update(start, Duration::ZERO);
+            let mut pose = start;
+            for _ in 0..steps {
+                pose = smoothing.update(target, Duration::from_secs(1) / (60 * steps));
+            }
+            pose
+        };
+        let (fast, slow) = (step(2), step(1));
+        assert!((fast.position - slow.position).length() < 1e-4);
+        assert!((fast.yaw - slow.yaw).abs() < 1e-5);
+        assert!((fast.pitch - slow.pitch).abs() < 1e-5);
+    }
+
+    #[test]
+    fn smoothing_factor_stays_below_one() {
+        assert_eq!(Smoothing::new(1.0).factor(), MAX_SMOOTHING);
+        assert_eq!(Smoothing::new(-1.0).factor(), 0.0);
+    }
+}
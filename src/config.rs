@@ -0,0 +1,29 @@
+//! Startup configuration for the engine window.
+
+/// Window title, size and background color, kept in one place so callers
+/// can change them without touching the graph setup.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowConfig {
+    pub title: String,
+    pub width: u32,
+    pub height: u32,
+    /// RGBA color the surface is cleared to each frame.
+    pub clear_color: [f32; 4],
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        WindowConfig {
+            title: "Rust Game Engine".to_owned(),
+            width: 800,
+            height: 600,
+            clear_color: [0.0, 0.0, 0.0, 1.0],
+        }
+    }
+}
+
+impl WindowConfig {
+    pub fn aspect(&self) -> f32 {
+        self.width as f32 / self.height.max(1) as f32
+    }
+}
@@ -0,0 +1,271 @@
+//! CPU-side visibility culling.
+
+use crate::math::{Mat4, Vec3};
+
+/// An axis-aligned bounding box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Aabb { min, max }
+    }
+
+    /// The eight corners, indexed by bit 0 = x, bit 1 = y, bit 2 = z.
+    pub fn corners(&self) -> [Vec3; 8] {
+        let mut corners = [Vec3::ZERO; 8];
+        for (i, corner) in corners.iter_mut().enumerate() {
+            *corner = Vec3::new(
+                if i & 1 == 0 { self.min.x } else { self.max.x },
+                if i & 2 == 0 { self.min.y } else { self.max.y },
+                if i & 4 == 0 { self.min.z } else { self.max.z },
+            );
+        }
+        corners
+    }
+}
+
+/// The two triangles of each box face, as indices into `Aabb::corners`.
+const BOX_TRIANGLES: [[usize; 3]; 12] = [
+    [0, 2, 3],
+    [0, 3, 1],
+    [4, 5, 7],
+    [4, 7, 6],
+    [0, 1, 5],
+    [0, 5, 4],
+    [2, 6, 7],
+    [2, 7, 3],
+    [0, 4, 6],
+    [0, 6, 2],
+    [1, 3, 7],
+    [1, 7, 5],
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OcclusionStats {
+    pub tested: usize,
+    pub culled: usize,
+}
+
+/// Software occlusion culling against a coarse depth buffer.
+///
+/// Each frame, large occluders are rasterized into a low-resolution depth
+/// buffer, then the screen bounds of every other object are tested against
+/// it. An object is culled only when every pixel under its bounds already
+/// holds nearer geometry. Depth follows the [0, 1] clip convention with
+/// nearer values smaller.
+///
+/// This costs CPU time for every occluder and occludee, so it is meant to be
+/// enabled only for dense scenes where it pays off.
+#[derive(Clone, Debug)]
+pub struct OcclusionBuffer {
+    width: u32,
+    height: u32,
+    depth: Vec<f32>,
+    view_projection: Mat4,
+    stats: OcclusionStats,
+}
+
+impl OcclusionBuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        OcclusionBuffer {
+            width,
+            height,
+            depth: vec![1.0; (width * height) as usize],
+            view_projection: Mat4::IDENTITY,
+            stats: OcclusionStats::default(),
+        }
+    }
+
+    /// Clears the buffer for a new frame seen through `view_projection`.
+    pub fn begin(&mut self, view_projection: Mat4) {
+        self.view_projection = view_projection;
+        self.stats = OcclusionStats::default();
+        for depth in &mut self.depth {
+            *depth = 1.0;
+        }
+    }
+
+    /// Rasterizes a box that hides what is behind it.
+    ///
+    /// Boxes reaching in front of the near plane are skipped rather than
+    /// clipped; leaving an occluder out only makes culling less aggressive,
+    /// never wrong.
+    pub fn add_occluder(&mut self, bounds: &Aabb) {
+        let mut screen = [(0.0, 0.0, 0.0); 8];
+        for (out, corner) in screen.iter_mut().zip(bounds.corners().iter()) {
+            match self.project(*corner) {
+                Some(point) if point.2 >= 0.0 => *out = point,
+                _ => return,
+            }
+        }
+        for triangle in &BOX_TRIANGLES {
+            self.rasterize(
+                screen[triangle[0]],
+                screen[triangle[1]],
+                screen[triangle[2]],
+            );
+        }
+    }
+
+    /// Returns `true` if `bounds` may be visible and should be drawn.
+    pub fn test(&mut self, bounds: &Aabb) -> bool {
+        self.stats.tested += 1;
+        let mut min_x = f32::INFINITY;
+        let mut min_y = f32::INFINITY;
+        let mut max_x = f32::NEG_INFINITY;
+        let mut max_y = f32::NEG_INFINITY;
+        let mut nearest = f32::INFINITY;
+        for corner in bounds.corners().iter() {
+            let (x, y, z) = match self.project(*corner) {
+                Some(point) => point,
+                None => return true,
+            };
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+            nearest = nearest.min(z);
+        }
+
+        // Widen by a pixel so partial coverage at occluder edges never hides
+        // an object that is only just peeking out.
+        let x0 = (min_x.floor() as i64 - 1).max(0);
+        let y0 = (min_y.floor() as i64 - 1).max(0);
+        let x1 = (max_x.ceil() as i64 + 1).min(self.width as i64 - 1);
+        let y1 = (max_y.ceil() as i64 + 1).min(self.height as i64 - 1);
+        if x0 > x1 || y0 > y1 {
+            // Entirely off screen; frustum culling is responsible for that.
+            return true;
+        }
+
+        for y in y0..=y1 {
+            let row = (y * self.width as i64) as usize;
+            for x in x0..=x1 {
+                if self.depth[row + x as usize] >= nearest {
+                    return true;
+                }
+            }
+        }
+        self.stats.culled += 1;
+        false
+    }
+
+    pub fn stats(&self) -> OcclusionStats {
+        self.stats
+    }
+
+    /// Projects to pixel coordinates and depth, or `None` behind the camera.
+    ///
+    /// Depth is left unclamped, so points between the eye and the near plane
+    /// come out negative.
+    fn project(&self, point: Vec3) -> Option<(f32, f32, f32)> {
+        let [x, y, z, w] = self.view_projection.transform_point(point);
+        if w <= f32::EPSILON {
+            return None;
+        }
+        Some((
+            (x / w + 1.0) / 2.0 * self.width as f32,
+            (y / w + 1.0) / 2.0 * self.height as f32,
+            z / w,
+        ))
+    }
+
+    fn rasterize(&mut self, a: (f32, f32, f32), b: (f32, f32, f32), c: (f32, f32, f32)) {
+        let area = edge(a, b, c.0, c.1);
+        if area.abs() <= f32::EPSILON {
+            return;
+        }
+        // Clamp in signed space; a triangle left of or above the screen has
+        // negative maximum bounds.
+        let x0 = (a.0.min(b.0).min(c.0).floor() as i64).max(0);
+        let y0 = (a.1.min(b.1).min(c.1).floor() as i64).max(0);
+        let x1 = (a.0.max(b.0).max(c.0).ceil() as i64).min(self.width as i64);
+        let y1 = (a.1.max(b.1).max(c.1).ceil() as i64).min(self.height as i64);
+        if x1 <= x0 || y1 <= y0 {
+            return;
+        }
+
+        for y in y0 as u32..y1 as u32 {
+            for x in x0 as u32..x1 as u32 {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let wa = edge(b, c, px, py) / area;
+                let wb = edge(c, a, px, py) / area;
+                let wc = edge(a, b, px, py) / area;
+                if wa < 0.0 || wb < 0.0 || wc < 0.0 {
+                    continue;
+                }
+                let depth = wa * a.2 + wb * b.2 + wc * c.2;
+                let texel = &mut self.depth[(y * self.width + x) as usize];
+                if depth < *texel {
+                    *texel = depth;
+                }
+            }
+        }
+    }
+}
+
+/// Twice the signed area of the triangle `a`, `b`, `(x, y)`.
+fn edge(a: (f32, f32, f32), b: (f32, f32, f32), x: f32, y: f32) -> f32 {
+    (b.0 - a.0) * (y - a.1) - (b.1 - a.1) * (x - a.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer() -> OcclusionBuffer {
+        let view = Mat4::look_to(Vec3::ZERO, -Vec3::Z, Vec3::Y);
+        let mut buffer = OcclusionBuffer::new(64, 64);
+        buffer.begin(Mat4::perspective(1.0, 1.0, 0.1, 100.0) * view);
+        buffer
+    }
+
+    fn slab(x: (f32, f32), z: (f32, f32)) -> Aabb {
+        Aabb::new(Vec3::new(x.0, -5.0, z.0), Vec3::new(x.1, 5.0, z.1))
+    }
+
+    #[test]
+    fn wall_hides_only_what_is_fully_behind_it() {
+        let mut buffer = buffer();
+        buffer.add_occluder(&slab((-5.0, 5.0), (-10.5, -10.0)));
+
+        assert!(!buffer.test(&slab((-1.0, 1.0), (-21.0, -20.0))));
+        assert!(buffer.test(&slab((-1.0, 1.0), (-6.0, -5.0))));
+        // Sticks out past the wall's right edge.
+        assert!(buffer.test(&slab((6.0, 14.0), (-21.0, -20.0))));
+        assert_eq!(
+            buffer.stats(),
+            OcclusionStats {
+                tested: 3,
+                culled: 1
+            }
+        );
+
+        buffer.begin(buffer.view_projection);
+        assert_eq!(buffer.stats(), OcclusionStats::default());
+        assert!(buffer.test(&slab((-1.0, 1.0), (-21.0, -20.0))));
+    }
+
+    #[test]
+    fn off_screen_occluder_is_rasterized_without_effect() {
+        let mut buffer = buffer();
+        buffer.add_occluder(&slab((-40.0, -30.0), (-10.5, -10.0)));
+        buffer.add_occluder(&Aabb::new(
+            Vec3::new(-40.0, -40.0, -10.5),
+            Vec3::new(-30.0, -30.0, -10.0),
+        ));
+        assert!(buffer.test(&slab((-1.0, 1.0), (-21.0, -20.0))));
+    }
+
+    #[test]
+    fn occluder_crossing_the_near_plane_is_skipped() {
+        let mut buffer = buffer();
+        buffer.add_occluder(&slab((-5.0, 5.0), (-10.0, -0.05)));
+        assert!(buffer.test(&slab((-1.0, 1.0), (-21.0, -20.0))));
+        assert_eq!(buffer.stats().culled, 0);
+    }
+}
@@ -0,0 +1,145 @@
+//! Geometry for debug visualizations drawn with the line renderer.
+
+use crate::math::Vec3;
+
+/// One end of a line segment.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 4],
+}
+
+/// A reference grid on the XZ plane, centered on the origin.
+///
+/// Lines are only generated within `fade_distance` of the camera and fade out
+/// toward that distance, so the cost stays bounded however large `size` is.
+/// The grid is meant to be drawn depth tested so the scene occludes it.
+#[derive(Clone, Copy, Debug)]
+pub struct Grid {
+    /// Number of cells along each side.
+    pub size: u32,
+    /// Distance between neighbouring lines.
+    pub spacing: f32,
+    /// Every `major_every`-th line, counted from the grid's edge, is drawn
+    /// with `major_color`.
+    pub major_every: u32,
+    pub major_color: [f32; 4],
+    pub minor_color: [f32; 4],
+    pub fade_distance: f32,
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Grid {
+            size: 100,
+            spacing: 1.0,
+            major_every: 10,
+            major_color: [0.6, 0.6, 0.6, 1.0],
+            minor_color: [0.35, 0.35, 0.35, 0.6],
+            fade_distance: 50.0,
+        }
+    }
+}
+
+impl Grid {
+    /// Builds the line list for a camera at `camera`, two vertices per segment.
+    pub fn lines(&self, camera: Vec3) -> Vec<LineVertex> {
+        // Work in line indices counted from the -X/-Z edge so odd sizes still
+        // span the full `size * spacing` extent.
+        let extent = self.size as f32 * self.spacing / 2.0;
+        let last_line = i64::from(self.size);
+        let visible = |center: f32| {
+            let first = ((center - self.fade_distance + extent) / self.spacing).floor() as i64;
+            let last = ((center + self.fade_distance + extent) / self.spacing).ceil() as i64;
+            (first.max(0), last.min(last_line))
+        };
+        let (x_first, x_last) = visible(camera.x);
+        let (z_first, z_last) = visible(camera.z);
+
+        let mut vertices = Vec::new();
+        if x_first > x_last || z_first > z_last {
+            return vertices;
+        }
+
+        // Lines are split at every crossing so the per-vertex fade follows
+        // the distance to the camera along their length.
+        for x in x_first..=x_last {
+            let color = self.line_color(x);
+            for z in z_first..z_last {
+                self.push_segment(&mut vertices, camera, color, (x, z), (x, z + 1));
+            }
+        }
+        for z in z_first..=z_last {
+            let color = self.line_color(z);
+            for x in x_first..x_last {
+                self.push_segment(&mut vertices, camera, color, (x, z), (x + 1, z));
+            }
+        }
+        vertices
+    }
+
+    fn line_color(&self, index: i64) -> [f32; 4] {
+        if self.major_every > 0 && index % self.major_every as i64 == 0 {
+            self.major_color
+        } else {
+            self.minor_color
+        }
+    }
+
+    fn push_segment(
+        &self,
+        vertices: &mut Vec<LineVertex>,
+        camera: Vec3,
+        color: [f32; 4],
+        start: (i64, i64),
+        end: (i64, i64),
+    ) {
+        let ground = Vec3::new(camera.x, 0.0, camera.z);
+        let extent = self.size as f32 * self.spacing / 2.0;
+        let point = |(x, z): (i64, i64)| {
+            Vec3::new(
+                x as f32 * self.spacing - extent,
+                0.0,
+                z as f32 * self.spacing - extent,
+            )
+        };
+        let (start, end) = (point(start), point(end));
+        let fade = |point: Vec3| {
+            let distance = (point - ground).length();
+            let [r, g, b, a] = color;
+            [r, g, b, a * (1.0 - distance / self.fade_distance).max(0.0)]
+        };
+        vertices.push(LineVertex {
+            position: start.to_array(),
+            color: fade(start),
+        });
+        vertices.push(LineVertex {
+            position: end.to_array(),
+            color: fade(end),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn odd_sizes_span_the_full_extent() {
+        let grid = Grid {
+            size: 3,
+            spacing: 2.0,
+            fade_distance: 100.0,
+            ..Grid::default()
+        };
+        let vertices = grid.lines(Vec3::ZERO);
+        // Four lines each way, split into three segments apiece.
+        assert_eq!(vertices.len(), 2 * 4 * 3 * 2);
+        for axis in [0, 2] {
+            let coords = vertices.iter().map(|v| v.position[axis]);
+            let min = coords.clone().fold(f32::INFINITY, f32::min);
+            let max = coords.fold(f32::NEG_INFINITY, f32::max);
+            assert_eq!((min, max), (-3.0, 3.0));
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! Exposure control for the tonemap pass.
+
+use std::fmt;
+use std::time::Duration;
+
+/// Scene luminance that auto-exposure maps to middle grey.
+const MIDDLE_GREY: f32 = 0.18;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExposureMode {
+    /// A fixed multiplier applied before tonemapping.
+    Manual(f32),
+    /// Adapts toward the scene's average luminance over time.
+    Auto,
+}
+
+/// Returned by [`Exposure::set_range`] when the bounds are unusable.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InvalidRange {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl fmt::Display for InvalidRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "exposure range {}..{} must be positive with min <= max",
+            self.min, self.max
+        )
+    }
+}
+
+impl std::error::Error for InvalidRange {}
+
+/// Produces the exposure multiplier fed to the tonemap pass each frame.
+#[derive(Clone, Copy, Debug)]
+pub struct Exposure {
+    pub mode: ExposureMode,
+    /// How quickly auto-exposure converges, as a rate per second.
+    pub adaptation_speed: f32,
+    min: f32,
+    max: f32,
+    current: f32,
+}
+
+impl Default for Exposure {
+    fn default() -> Self {
+        Exposure {
+            mode: ExposureMode::Manual(1.0),
+            adaptation_speed: 1.5,
+            min: 1.0 / 64.0,
+            max: 64.0,
+            current: 1.0,
+        }
+    }
+}
+
+impl Exposure {
+    /// Advances the exposure and returns the multiplier for this frame.
+    ///
+    /// `average_luminance` is the scene's geometric mean luminance, as read
+    /// back from the luminance reduction. Auto mode holds its current value
+    /// while none is available.
+    pub fn update(&mut self, average_luminance: Option<f32>, delta: Duration) -> f32 {
+        match self.mode {
+            ExposureMode::Manual(exposure) => self.current = exposure,
+            ExposureMode::Auto => {
+                if let Some(luminance) = average_luminance.filter(|&l| l > 0.0) {
+                    // Blend in log space so brightening and darkening adapt
+                    // at the same perceived rate.
+                    let target = (MIDDLE_GREY / luminance).log2();
+                    let t = 1.0 - (-self.adaptation_speed * delta.as_secs_f32()).exp();
+                    let current = self.current.log2();
+                    self.current = (current + (target - current) * t).exp2();
+                }
+            }
+        }
+        self.current = self.current.clamp(self.min, self.max);
+        self.current
+    }
+
+    /// Sets the bounds the exposure is clamped to.
+    ///
+    /// Both must be positive, since auto mode adapts in log space, and
+    /// `min` must not exceed `max`.
+    pub fn set_range(&mut self, min: f32, max: f32) -> Result<(), InvalidRange> {
+        if !(min > 0.0 && min <= max && max.is_finite()) {
+            return Err(InvalidRange { min, max });
+        }
+        self.min = min;
+        self.max = max;
+        self.current = self.current.clamp(min, max);
+        Ok(())
+    }
+
+    pub fn range(&self) -> (f32, f32) {
+        (self.min, self.max)
+    }
+
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+}
+
+/// Geometric mean luminance of linear RGB pixels, matching what the GPU
+/// reduction computes.
+pub fn average_luminance(pixels: &[[f32; 3]]) -> f32 {
+    if pixels.is_empty() {
+        return 0.0;
+    }
+    let sum: f32 = pixels
+        .iter()
+        .map(|&[r, g, b]| (0.2126 * r + 0.7152 * g + 0.0722 * b).max(1e-4).ln())
+        .sum();
+    (sum / pixels.len() as f32).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_range_rejects_unusable_bounds() {
+        let mut exposure = Exposure::default();
+        for (min, max) in [
+            (0.0, 1.0),
+            (-1.0, 1.0),
+            (2.0, 1.0),
+            (f32::NAN, 1.0),
+            (1.0, f32::INFINITY),
+        ] {
+            assert!(exposure.set_range(min, max).is_err());
+        }
+        assert_eq!(exposure.range(), (1.0 / 64.0, 64.0));
+
+        exposure.set_range(2.0, 8.0).unwrap();
+        assert_eq!(exposure.current(), 2.0);
+        exposure.mode = ExposureMode::Auto;
+        let value = exposure.update(Some(1e-6), Duration::from_secs(10));
+        assert_eq!(value, 8.0);
+    }
+}
@@ -0,0 +1,345 @@
+//! Input handling shared by the event loop and game code.
+
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+
+/// Keyboard and cursor state, updated from window events and queried by
+/// game code.
+///
+/// Generic over the key type so it can hold the windowing library's key
+/// codes directly. Call `end_frame` once per loop iteration after the frame
+/// has been processed to clear the per-frame edges.
+#[derive(Clone, Debug)]
+pub struct InputState<K> {
+    down: HashSet<K>,
+    just_pressed: HashSet<K>,
+    just_released: HashSet<K>,
+    cursor: Option<(f64, f64)>,
+}
+
+impl<K> Default for InputState<K> {
+    fn default() -> Self {
+        InputState {
+            down: HashSet::new(),
+            just_pressed: HashSet::new(),
+            just_released: HashSet::new(),
+            cursor: None,
+        }
+    }
+}
+
+impl<K: Copy + Eq + Hash> InputState<K> {
+    pub fn new() -> Self {
+        InputState::default()
+    }
+
+    /// Records a key press or release. Auto-repeat presses for a key that is
+    /// already down are ignored, so a held key reports `key_just_pressed`
+    /// only on the frame it went down.
+    pub fn handle_key(&mut self, key: K, pressed: bool) {
+        if pressed {
+            if self.down.insert(key) {
+                self.just_pressed.insert(key);
+            }
+        } else if self.down.remove(&key) {
+            self.just_released.insert(key);
+        }
+    }
+
+    pub fn handle_cursor(&mut self, x: f64, y: f64) {
+        self.cursor = Some((x, y));
+    }
+
+    /// Forgets the cursor, for when it leaves the window.
+    pub fn clear_cursor(&mut self) {
+        self.cursor = None;
+    }
+
+    /// Releases every key, for when the window loses focus and release
+    /// events would otherwise be missed.
+    pub fn release_all(&mut self) {
+        self.just_released.extend(self.down.drain());
+    }
+
+    pub fn is_key_down(&self, key: K) -> bool {
+        self.down.contains(&key)
+    }
+
+    pub fn key_just_pressed(&self, key: K) -> bool {
+        self.just_pressed.contains(&key)
+    }
+
+    pub fn key_just_released(&self, key: K) -> bool {
+        self.just_released.contains(&key)
+    }
+
+    pub fn cursor_position(&self) -> Option<(f64, f64)> {
+        self.cursor
+    }
+
+    /// Clears the just-pressed and just-released sets.
+    pub fn end_frame(&mut self) {
+        self.just_pressed.clear();
+        self.just_released.clear();
+    }
+}
+
+/// An input event that can be written to and read back from a recording.
+pub trait RecordedEvent: Sized {
+    /// Encodes the event as a single line of text without newlines.
+    fn encode(&self) -> String;
+
+    fn decode(line: &str) -> Option<Self>;
+}
+
+#[derive(Debug)]
+pub enum RecordingError {
+    Io(io::Error),
+    /// A line that could not be parsed, with its 1-based line number.
+    Malformed(usize),
+}
+
+impl fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecordingError::Io(err) => write!(f, "input recording I/O error: {}", err),
+            RecordingError::Malformed(line) => {
+                write!(f, "malformed input recording at line {}", line)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RecordingError {}
+
+impl From<io::Error> for RecordingError {
+    fn from(err: io::Error) -> Self {
+        RecordingError::Io(err)
+    }
+}
+
+/// Input events stamped with the time since recording started.
+///
+/// Timestamps should come from the simulation clock rather than the wall
+/// clock; with a fixed timestep, playback then delivers each event on the
+/// same simulation step it was recorded on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recording<E> {
+    events: Vec<(Duration, E)>,
+}
+
+impl<E> Default for Recording<E> {
+    fn default() -> Self {
+        Recording { events: Vec::new() }
+    }
+}
+
+impl<E: RecordedEvent> Recording<E> {
+    pub fn new() -> Self {
+        Recording::default()
+    }
+
+    /// Appends an event. Timestamps must not go backwards.
+    pub fn record(&mut self, time: Duration, event: E) {
+        debug_assert!(self.events.last().is_none_or(|(last, _)| *last <= time));
+        self.events.push((time, event));
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Writes one `<microseconds> <event>` line per event.
+    pub fn save<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for (time, event) in &self.events {
+            writeln!(writer, "{} {}", time.as_micros(), event.encode())?;
+        }
+        writer.flush()
+    }
+
+    pub fn load<R: BufRead>(reader: R) -> Result<Self, RecordingError> {
+        let mut recording = Recording::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let malformed = || RecordingError::Malformed(index + 1);
+            let (time, event) = line.split_once(' ').ok_or_else(malformed)?;
+            let time = time.parse::<u64>().map_err(|_| malformed())?;
+            let event = E::decode(event).ok_or_else(malformed)?;
+            let time = Duration::from_micros(time);
+            if recording
+                .events
+                .last()
+                .is_some_and(|(last, _)| *last > time)
+            {
+                return Err(malformed());
+            }
+            recording.events.push((time, event));
+        }
+        Ok(recording)
+    }
+
+    pub fn playback(self) -> Playback<E> {
+        Playback {
+            events: self.events.into(),
+        }
+    }
+}
+
+/// Replays a recording in step with the engine's clock.
+#[derive(Debug)]
+pub struct Playback<E> {
+    events: VecDeque<(Duration, E)>,
+}
+
+impl<E> Playback<E> {
+    /// Removes and returns every event recorded at or before `time`, so the
+    /// caller can feed them through the same path as live input.
+    pub fn due(&mut self, time: Duration) -> Vec<E> {
+        let mut due = Vec::new();
+        while self.events.front().is_some_and(|(at, _)| *at <= time) {
+            if let Some((_, event)) = self.events.pop_front() {
+                due.push(event);
+            }
+        }
+        due
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum Event {
+        Key(u32, bool),
+        Mouse(f32, f32),
+    }
+
+    impl RecordedEvent for Event {
+        fn encode(&self) -> String {
+            match self {
+                Event::Key(code, pressed) => format!("key {} {}", code, pressed),
+                Event::Mouse(x, y) => format!("mouse {} {}", x, y),
+            }
+        }
+
+        fn decode(line: &str) -> Option<Self> {
+            let parts: Vec<&str> = line.split(' ').collect();
+            match parts.as_slice() {
+                ["key", code, pressed] => {
+                    Some(Event::Key(code.parse().ok()?, pressed.parse().ok()?))
+                }
+                ["mouse", x, y] => Some(Event::Mouse(x.parse().ok()?, y.parse().ok()?)),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn just_pressed_lasts_one_frame() {
+        let mut input = InputState::new();
+        input.handle_key(7u32, true);
+        assert!(input.key_just_pressed(7));
+        assert!(input.is_key_down(7));
+
+        input.end_frame();
+        assert!(!input.key_just_pressed(7));
+        assert!(input.is_key_down(7));
+    }
+
+    #[test]
+    fn key_repeat_does_not_fire_again() {
+        let mut input = InputState::new();
+        input.handle_key(7u32, true);
+        input.end_frame();
+        input.handle_key(7, true);
+        assert!(!input.key_just_pressed(7));
+        assert!(input.is_key_down(7));
+
+        input.handle_key(7, false);
+        input.handle_key(7, false);
+        assert!(input.key_just_released(7));
+        assert!(!input.is_key_down(7));
+    }
+
+    #[test]
+    fn release_all_reports_released_keys() {
+        let mut input = InputState::new();
+        input.handle_key(1u32, true);
+        input.handle_key(2, true);
+        input.end_frame();
+
+        input.release_all();
+        for key in [1, 2] {
+            assert!(input.key_just_released(key));
+            assert!(!input.is_key_down(key));
+        }
+        assert!(!input.key_just_released(3));
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn saved_recording_replays_identically() {
+        let mut recording = Recording::new();
+        recording.record(ms(0), Event::Key(17, true));
+        recording.record(ms(16), Event::Mouse(3.5, -1.25));
+        recording.record(ms(16), Event::Mouse(0.5, 0.0));
+        recording.record(ms(48), Event::Key(17, false));
+
+        let mut saved = Vec::new();
+        recording.save(&mut saved).unwrap();
+        let loaded = Recording::<Event>::load(saved.as_slice()).unwrap();
+        assert_eq!(loaded, recording);
+
+        // Step at a fixed 1/60 s, as the simulation would.
+        let mut playback = loaded.playback();
+        let steps: Vec<Vec<Event>> = (0..4)
+            .map(|step| playback.due(Duration::from_micros(step * 16_667)))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                vec![Event::Key(17, true)],
+                vec![Event::Mouse(3.5, -1.25), Event::Mouse(0.5, 0.0)],
+                vec![],
+                vec![Event::Key(17, false)],
+            ]
+        );
+        assert!(playback.is_finished());
+    }
+
+    #[test]
+    fn load_rejects_out_of_order_timestamps() {
+        let data = "2000 key 1 true\n1000 key 1 false\n";
+        match Recording::<Event>::load(data.as_bytes()) {
+            Err(RecordingError::Malformed(line)) => assert_eq!(line, 2),
+            other => panic!("expected a malformed recording, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn load_rejects_undecodable_lines() {
+        let data = "1000 key 1 true\n\n2000 jump\n";
+        match Recording::<Event>::load(data.as_bytes()) {
+            Err(RecordingError::Malformed(line)) => assert_eq!(line, 3),
+            other => panic!("expected a malformed recording, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,20 @@
+pub mod budget;
+pub mod camera;
+pub mod config;
+pub mod culling;
+pub mod debug;
+pub mod exposure;
+pub mod input;
+pub mod lighting;
+pub mod logging;
+pub mod math;
+pub mod mesh;
+pub mod mesh_loader;
+pub mod profiler;
+pub mod quality;
+pub mod ring;
+pub mod scene;
+pub mod shader;
+pub mod time;
+pub mod viewport;
+pub mod watch;
@@ -0,0 +1,403 @@
+//! CPU-side light management for the lighting passes.
+
+use crate::math::Vec3;
+use std::cmp::Ordering;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PointLight {
+    pub position: Vec3,
+    pub color: [f32; 3],
+    pub intensity: f32,
+    /// Distance beyond which the light contributes nothing.
+    pub range: f32,
+}
+
+impl PointLight {
+    /// Rough contribution of the light at `point`, used to rank lights.
+    pub fn influence(&self, point: Vec3) -> f32 {
+        let distance = (self.position - point).length();
+        if distance > self.range {
+            return 0.0;
+        }
+        self.intensity / (1.0 + distance * distance)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LightStats {
+    pub considered: usize,
+    pub used: usize,
+}
+
+/// Picks at most `max_lights` lights with the most influence on `viewer`,
+/// returning their indices into `lights`, strongest first.
+///
+/// Lights that cannot reach the viewer position are dropped even when the
+/// cap is not reached.
+pub fn select_lights(
+    lights: &[PointLight],
+    viewer: Vec3,
+    max_lights: usize,
+) -> (Vec<usize>, LightStats) {
+    let mut ranked: Vec<(usize, f32)> = lights
+        .iter()
+        .enumerate()
+        .map(|(index, light)| (index, light.influence(viewer)))
+        .filter(|&(_, influence)| influence > 0.0)
+        .collect();
+    let by_influence =
+        |a: &(usize, f32), b: &(usize, f32)| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal);
+    if ranked.len() > max_lights {
+        if max_lights > 0 {
+            ranked.select_nth_unstable_by(max_lights - 1, by_influence);
+        }
+        ranked.truncate(max_lights);
+    }
+    ranked.sort_by(by_influence);
+
+    let stats = LightStats {
+        considered: lights.len(),
+        used: ranked.len(),
+    };
+    (ranked.into_iter().map(|(index, _)| index).collect(), stats)
+}
+
+/// Below this many lights, shading every light per pixel is cheaper than
+/// binning them into tiles.
+pub const TILED_LIGHTING_THRESHOLD: usize = 32;
+
+pub fn use_tiled_lighting(light_count: usize) -> bool {
+    light_count >= TILED_LIGHTING_THRESHOLD
+}
+
+/// Perspective parameters needed to project lights onto the screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TileProjection {
+    pub width: u32,
+    pub height: u32,
+    /// Vertical field of view in radians.
+    pub fov_y: f32,
+    pub near: f32,
+}
+
+/// Screen-space tiles each holding the indices of the lights that touch them.
+///
+/// Lights are given in view space with the camera looking down -Z. Each
+/// light's sphere is projected to a conservative screen rectangle and
+/// appended to every tile it overlaps, so the lighting shader only iterates
+/// the lists for its own tile.
+#[derive(Clone, Debug)]
+pub struct TileGrid {
+    pub tile_size: u32,
+    tiles_x: u32,
+    tiles_y: u32,
+    lists: Vec<Vec<u32>>,
+}
+
+impl TileGrid {
+    pub fn new(tile_size: u32) -> Self {
+        TileGrid {
+            tile_size: tile_size.max(1),
+            tiles_x: 0,
+            tiles_y: 0,
+            lists: Vec::new(),
+        }
+    }
+
+    pub fn tiles_x(&self) -> u32 {
+        self.tiles_x
+    }
+
+    pub fn tiles_y(&self) -> u32 {
+        self.tiles_y
+    }
+
+    /// Rebins `lights`, given as view-space positions and ranges.
+    pub fn assign(&mut self, projection: TileProjection, lights: &[(Vec3, f32)]) {
+        self.resize(projection.width, projection.height);
+        self.bin(projection, lights, None);
+    }
+
+    /// Rebins `lights` like `assign`, additionally rejecting lights whose
+    /// depth range misses the geometry in a tile.
+    ///
+    /// `depth` holds the depth prepass output as linear view-space depth, one
+    /// value per pixel in rows from the top of the screen. Cleared pixels
+    /// should hold `f32::INFINITY`.
+    pub fn assign_with_depth(
+        &mut self,
+        projection: TileProjection,
+        lights: &[(Vec3, f32)],
+        depth: &[f32],
+    ) {
+        debug_assert_eq!(depth.len(), (projection.width * projection.height) as usize);
+        self.resize(projection.width, projection.height);
+        let bounds = self.depth_bounds(projection, depth);
+        self.bin(projection, lights, Some(&bounds));
+    }
+
+    fn bin(
+        &mut self,
+        projection: TileProjection,
+        lights: &[(Vec3, f32)],
+        depth_bounds: Option<&[(f32, f32)]>,
+    ) {
+        for list in &mut self.lists {
+            list.clear();
+        }
+
+        let focal = 1.0 / (projection.fov_y / 2.0).tan();
+        let aspect = projection.width as f32 / projection.height.max(1) as f32;
+        for (index, &(position, range)) in lights.iter().enumerate() {
+            let depth = -position.z;
+            if depth + range < projection.near {
+                continue;
+            }
+
+            let (x0, y0, x1, y1) = if depth - range <= projection.near {
+                // The sphere crosses the near plane and may cover any tile.
+                (0, 0, self.tiles_x - 1, self.tiles_y - 1)
+            } else {
+                // Project the light's bounding box: x / depth is monotonic
+                // in both terms, so its corners bound the whole sphere.
+                let depths = [depth - range, depth + range];
+                let project = |offset: f32, scale: f32| {
+                    let near = offset * scale / depths[0];
+                    let far = offset * scale / depths[1];
+                    (near.min(far), near.max(far))
+                };
+                let scale_x = focal / aspect;
+                let (left, _) = project(position.x - range, scale_x);
+                let (_, right) = project(position.x + range, scale_x);
+                let (bottom, _) = project(position.y - range, focal);
+                let (_, top) = project(position.y + range, focal);
+                match self.tile_rect(projection, (left, right), (bottom, top)) {
+                    Some(rect) => rect,
+                    None => continue,
+                }
+            };
+
+            for tile_y in y0..=y1 {
+                for tile_x in x0..=x1 {
+                    let tile = (tile_y * self.tiles_x + tile_x) as usize;
+                    if let Some(bounds) = depth_bounds {
+                        let (min, max) = bounds[tile];
+                        if depth + range < min || depth - range > max {
+                            continue;
+                        }
+                    }
+                    self.lists[tile].push(index as u32);
+                }
+            }
+        }
+    }
+
+    /// Indices of the lights touching the given tile.
+    pub fn lights(&self, tile_x: u32, tile_y: u32) -> &[u32] {
+        &self.lists[(tile_y * self.tiles_x + tile_x) as usize]
+    }
+
+    /// Total light references across all tiles and the longest tile list.
+    pub fn stats(&self) -> (usize, usize) {
+        let total = self.lists.iter().map(Vec::len).sum();
+        let longest = self.lists.iter().map(Vec::len).max().unwrap_or(0);
+        (total, longest)
+    }
+
+    /// Minimum and maximum depth of each tile. Tiles without any geometry get
+    /// an empty range that no light overlaps.
+    fn depth_bounds(&self, projection: TileProjection, depth: &[f32]) -> Vec<(f32, f32)> {
+        let mut bounds = vec![(f32::INFINITY, f32::NEG_INFINITY); self.lists.len()];
+        let width = projection.width as usize;
+        for (i, &value) in depth.iter().enumerate() {
+            if !value.is_finite() {
+                continue;
+            }
+            let tile_x = (i % width) as u32 / self.tile_size;
+            let tile_y = (i / width) as u32 / self.tile_size;
+            let (min, max) = &mut bounds[(tile_y * self.tiles_x + tile_x) as usize];
+            *min = min.min(value);
+            *max = max.max(value);
+        }
+        bounds
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.tiles_x = width.div_ceil(self.tile_size).max(1);
+        self.tiles_y = height.div_ceil(self.tile_size).max(1);
+        self.lists
+            .resize_with((self.tiles_x * self.tiles_y) as usize, Vec::new);
+    }
+
+    /// Converts an NDC rectangle (y up) into an inclusive tile range, or
+    /// `None` if it lies entirely off screen.
+    fn tile_rect(
+        &self,
+        projection: TileProjection,
+        (left, right): (f32, f32),
+        (bottom, top): (f32, f32),
+    ) -> Option<(u32, u32, u32, u32)> {
+        if right < -1.0 || left > 1.0 || top < -1.0 || bottom > 1.0 {
+            return None;
+        }
+        let tile = self.tile_size as f32;
+        let to_tile_x = |ndc: f32| {
+            let pixel = (ndc.clamp(-1.0, 1.0) + 1.0) / 2.0 * projection.width as f32;
+            ((pixel / tile) as u32).min(self.tiles_x - 1)
+        };
+        let to_tile_y = |ndc: f32| {
+            let pixel = (1.0 - ndc.clamp(-1.0, 1.0)) / 2.0 * projection.height as f32;
+            ((pixel / tile) as u32).min(self.tiles_y - 1)
+        };
+        Some((
+            to_tile_x(left),
+            to_tile_y(top),
+            to_tile_x(right),
+            to_tile_y(bottom),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn light(x: f32, intensity: f32, range: f32) -> PointLight {
+        PointLight {
+            position: Vec3::new(x, 0.0, 0.0),
+            color: [1.0; 3],
+            intensity,
+            range,
+        }
+    }
+
+    #[test]
+    fn selects_the_strongest_lights_strongest_first() {
+        // Intensities are a shuffled 1..=100, so the strongest 16 are known.
+        let lights: Vec<PointLight> = (0..100)
+            .map(|i| light(1.0, ((i * 37) % 100 + 1) as f32, 10.0))
+            .collect();
+        let (selected, stats) = select_lights(&lights, Vec3::ZERO, 16);
+        let intensities: Vec<f32> = selected.iter().map(|&i| lights[i].intensity).collect();
+        let expected: Vec<f32> = (85..=100).rev().map(|i| i as f32).collect();
+        assert_eq!(intensities, expected);
+        assert_eq!(
+            stats,
+            LightStats {
+                considered: 100,
+                used: 16
+            }
+        );
+
+        let (selected, stats) = select_lights(&lights, Vec3::ZERO, 0);
+        assert!(selected.is_empty());
+        assert_eq!(stats.used, 0);
+    }
+
+    #[test]
+    fn lights_out_of_range_are_dropped() {
+        let lights = [
+            light(1.0, 1.0, 10.0),
+            light(50.0, 100.0, 10.0),
+            light(-2.0, 5.0, 3.0),
+        ];
+        let (selected, stats) = select_lights(&lights, Vec3::ZERO, 16);
+        assert_eq!(selected, vec![2, 0]);
+        assert_eq!(
+            stats,
+            LightStats {
+                considered: 3,
+                used: 2
+            }
+        );
+    }
+
+    const PROJECTION: TileProjection = TileProjection {
+        width: 160,
+        height: 96,
+        fov_y: 1.0,
+        near: 0.1,
+    };
+
+    fn every_tile(grid: &TileGrid) -> impl Iterator<Item = (u32, u32)> {
+        let (tiles_x, tiles_y) = (grid.tiles_x(), grid.tiles_y());
+        (0..tiles_y).flat_map(move |y| (0..tiles_x).map(move |x| (x, y)))
+    }
+
+    #[test]
+    fn light_crossing_the_near_plane_lands_in_every_tile() {
+        let mut grid = TileGrid::new(16);
+        grid.assign(PROJECTION, &[(Vec3::new(0.0, 0.0, -0.5), 2.0)]);
+        assert_eq!((grid.tiles_x(), grid.tiles_y()), (10, 6));
+        for (x, y) in every_tile(&grid) {
+            assert_eq!(grid.lights(x, y), &[0]);
+        }
+    }
+
+    #[test]
+    fn lights_only_land_in_tiles_they_overlap() {
+        let mut grid = TileGrid::new(16);
+        let lights = [
+            (Vec3::new(0.0, 0.0, -10.0), 1.0),
+            // Far off to the side and behind the camera respectively.
+            (Vec3::new(100.0, 0.0, -10.0), 1.0),
+            (Vec3::new(0.0, 0.0, 10.0), 1.0),
+        ];
+        grid.assign(PROJECTION, &lights);
+
+        let (total, longest) = grid.stats();
+        assert!(total > 0 && total < 60);
+        assert_eq!(longest, 1);
+        assert!(grid.lights(0, 0).is_empty());
+        assert_eq!(grid.lights(5, 3), &[0]);
+    }
+
+    #[test]
+    fn depth_bounds_reduce_per_tile_light_counts() {
+        // A row of lights at several depths, seen over a wall at depth 5
+        // that covers the left half of the screen; the right half is empty.
+        let lights: Vec<(Vec3, f32)> = (0..32)
+            .map(|i| {
+                let x = (i % 8) as f32 - 3.5;
+                let depth = 2.0 + (i / 8) as f32 * 3.0;
+                (Vec3::new(x * depth * 0.2, 0.0, -depth), 0.75)
+            })
+            .collect();
+        let width = PROJECTION.width as usize;
+        let depth: Vec<f32> = (0..width * PROJECTION.height as usize)
+            .map(|i| {
+                if i % width < width / 2 {
+                    5.0
+                } else {
+                    f32::INFINITY
+                }
+            })
+            .collect();
+
+        let mut plain = TileGrid::new(16);
+        plain.assign(PROJECTION, &lights);
+        let mut bounded = TileGrid::new(16);
+        bounded.assign_with_depth(PROJECTION, &lights, &depth);
+
+        let (without, _) = plain.stats();
+        let (with, _) = bounded.stats();
+        assert!(with <= without);
+        assert!(
+            with < without / 2,
+            "{} vs {} light references",
+            with,
+            without
+        );
+
+        for (x, y) in every_tile(&bounded) {
+            for light in bounded.lights(x, y) {
+                let (position, range) = lights[*light as usize];
+                assert!((-position.z - 5.0).abs() <= range);
+                assert!(plain.lights(x, y).contains(light));
+            }
+            if x >= bounded.tiles_x() / 2 {
+                assert!(bounded.lights(x, y).is_empty());
+            }
+        }
+    }
+}
@@ -0,0 +1,331 @@
+//! Console logging with an optional rotating log file alongside it.
+
+use std::env;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    /// Parses a level name such as `info` or `WARN`.
+    pub fn parse(name: &str) -> Option<Level> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "error" => Some(Level::Error),
+            "warn" => Some(Level::Warn),
+            "info" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            "trace" => Some(Level::Trace),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Where and how large the log file may grow.
+#[derive(Clone, Debug)]
+pub struct FileConfig {
+    pub path: PathBuf,
+    /// Size in bytes after which the file is rotated.
+    pub max_bytes: u64,
+    /// Number of rotated files kept as `<path>.1`, `<path>.2`, ...
+    pub backups: usize,
+}
+
+/// A log file that is renamed aside once it exceeds its size limit.
+pub struct RotatingFile {
+    config: FileConfig,
+    file: File,
+    written: u64,
+}
+
+impl RotatingFile {
+    pub fn open(config: FileConfig) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&config.path)?;
+        let written = file.metadata()?.len();
+        Ok(RotatingFile {
+            config,
+            file,
+            written,
+        })
+    }
+
+    fn backup_path(&self, index: usize) -> PathBuf {
+        let mut path = self.config.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        path.into()
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.config.backups == 0 {
+            self.file.set_len(0)?;
+        } else {
+            for index in (1..self.config.backups).rev() {
+                let from = self.backup_path(index);
+                if from.exists() {
+                    fs::rename(&from, self.backup_path(index + 1))?;
+                }
+            }
+            fs::rename(&self.config.path, self.backup_path(1))?;
+            self.file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.config.path)?;
+        }
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.config.max_bytes {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Per-target level filter in the style of `env_logger`'s `RUST_LOG`.
+///
+/// A spec is a comma-separated list of directives: a bare level sets the
+/// default, `target=level` sets the level for targets starting with
+/// `target`, and a bare target enables every level for it. `off` disables
+/// logging. The longest matching target wins. As in `env_logger`, a spec
+/// that names only targets leaves everything else off.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Filter {
+    default: Option<Level>,
+    directives: Vec<(String, Option<Level>)>,
+}
+
+impl Filter {
+    /// Allows everything up to `level` for every target.
+    pub fn new(level: Level) -> Self {
+        Filter {
+            default: Some(level),
+            directives: Vec::new(),
+        }
+    }
+
+    /// Parses a spec, returning the filter and any directives it ignored.
+    pub fn parse(spec: &str) -> (Filter, Vec<String>) {
+        let mut filter = Filter {
+            default: None,
+            directives: Vec::new(),
+        };
+        let mut ignored = Vec::new();
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let mut parts = directive.splitn(2, '=');
+            let name = parts.next().unwrap_or("").trim();
+            match parts.next() {
+                Some(level) => match parse_level(level) {
+                    Some(level) if !name.is_empty() && !name.contains('/') => {
+                        filter.directives.push((name.to_owned(), level))
+                    }
+                    _ => ignored.push(directive.to_owned()),
+                },
+                None => match parse_level(name) {
+                    Some(level) => filter.default = level,
+                    None if name.contains('/') => ignored.push(directive.to_owned()),
+                    None => filter
+                        .directives
+                        .push((name.to_owned(), Some(Level::Trace))),
+                },
+            }
+        }
+        filter.directives.sort_by_key(|(name, _)| name.len());
+        (filter, ignored)
+    }
+
+    pub fn enabled(&self, target: &str, level: Level) -> bool {
+        let max = self
+            .directives
+            .iter()
+            .rev()
+            .find(|(name, _)| target.starts_with(name.as_str()))
+            .map_or(self.default, |&(_, level)| level);
+        max.is_some_and(|max| level <= max)
+    }
+}
+
+/// Parses a level name, with `Some(None)` meaning `off`.
+fn parse_level(name: &str) -> Option<Option<Level>> {
+    if name.trim().eq_ignore_ascii_case("off") {
+        Some(None)
+    } else {
+        Level::parse(name).map(Some)
+    }
+}
+
+/// Writes log lines to stderr and, when configured, tees them to a file.
+///
+/// Messages the filter rejects are dropped. If the file cannot be opened or a
+/// write to it fails, the logger reports it once on stderr and carries on
+/// with console output only.
+pub struct Logger {
+    filter: Filter,
+    file: Mutex<Option<RotatingFile>>,
+}
+
+impl Logger {
+    pub fn new(filter: Filter, file: Option<FileConfig>) -> Self {
+        let file = file.and_then(|config| {
+            let path = config.path.clone();
+            RotatingFile::open(config)
+                .map_err(|err| {
+                    eprintln!(
+                        "Could not open log file {}, logging to console only: {}",
+                        path.display(),
+                        err
+                    )
+                })
+                .ok()
+        });
+        Logger {
+            filter,
+            file: Mutex::new(file),
+        }
+    }
+
+    /// Builds a logger filtered by `RUST_LOG`, allowing `Info` and above
+    /// when it is unset. Directives that cannot be parsed are reported on
+    /// stderr and skipped.
+    pub fn from_env(file: Option<FileConfig>) -> Self {
+        let filter = match env::var("RUST_LOG") {
+            Ok(spec) => {
+                let (filter, ignored) = Filter::parse(&spec);
+                for directive in ignored {
+                    eprintln!("Ignoring invalid RUST_LOG directive `{}`", directive);
+                }
+                filter
+            }
+            Err(_) => Filter::new(Level::Info),
+        };
+        Logger::new(filter, file)
+    }
+
+    pub fn enabled(&self, target: &str, level: Level) -> bool {
+        self.filter.enabled(target, level)
+    }
+
+    /// Logs `message` for `target`, usually the caller's `module_path!()`.
+    pub fn log(&self, target: &str, level: Level, message: fmt::Arguments) {
+        if !self.enabled(target, level) {
+            return;
+        }
+        let line = format!("[{} {}] {}\n", level, target, message);
+        eprint!("{}", line);
+
+        let mut file = self.file.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(sink) = file.as_mut() {
+            if let Err(err) = sink.write_all(line.as_bytes()) {
+                eprintln!("Log file write failed, logging to console only: {}", err);
+                *file = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process;
+
+    #[test]
+    fn bare_level_sets_the_default() {
+        let (filter, ignored) = Filter::parse("warn");
+        assert!(ignored.is_empty());
+        assert!(filter.enabled("anything", Level::Warn));
+        assert!(!filter.enabled("anything", Level::Info));
+    }
+
+    #[test]
+    fn target_directives_override_the_default() {
+        let (filter, ignored) =
+            Filter::parse("info, rust_game_engine=debug, rust_game_engine::ring=off");
+        assert!(ignored.is_empty());
+        assert!(filter.enabled("rust_game_engine::scene", Level::Debug));
+        assert!(!filter.enabled("rust_game_engine::scene", Level::Trace));
+        assert!(!filter.enabled("rust_game_engine::ring", Level::Error));
+        assert!(filter.enabled("other", Level::Info));
+        assert!(!filter.enabled("other", Level::Debug));
+    }
+
+    #[test]
+    fn target_only_spec_leaves_other_targets_off() {
+        let (filter, _) = Filter::parse("rust_game_engine");
+        assert!(filter.enabled("rust_game_engine::camera", Level::Trace));
+        assert!(!filter.enabled("other", Level::Error));
+    }
+
+    #[test]
+    fn invalid_directives_are_reported() {
+        let (filter, ignored) = Filter::parse("debug,foo=loud,=info,foo/bar");
+        assert_eq!(ignored, vec!["foo=loud", "=info", "foo/bar"]);
+        assert!(filter.enabled("foo", Level::Debug));
+    }
+
+    #[test]
+    fn rotates_past_max_bytes_and_shifts_backups() {
+        let dir = env::temp_dir().join(format!("rge-logging-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("engine.log");
+        let backup = |n: usize| dir.join(format!("engine.log.{}", n));
+        let mut file = RotatingFile::open(FileConfig {
+            path: path.clone(),
+            max_bytes: 10,
+            backups: 2,
+        })
+        .unwrap();
+
+        file.write_all(b"first-0001").unwrap();
+        assert!(!backup(1).exists());
+        file.write_all(b"second-002").unwrap();
+        assert_eq!(fs::read(backup(1)).unwrap(), b"first-0001");
+        file.write_all(b"third-0003").unwrap();
+        assert_eq!(fs::read(backup(2)).unwrap(), b"first-0001");
+        assert_eq!(fs::read(backup(1)).unwrap(), b"second-002");
+        file.write_all(b"fourth-004").unwrap();
+        file.flush().unwrap();
+
+        // The oldest backup falls off once `backups` are kept.
+        assert_eq!(fs::read(backup(2)).unwrap(), b"second-002");
+        assert_eq!(fs::read(backup(1)).unwrap(), b"third-0003");
+        assert_eq!(fs::read(&path).unwrap(), b"fourth-004");
+        assert!(!backup(3).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,204 @@
+//! Small linear algebra types used by the CPU-side engine code.
+
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Vec3 {
+    pub const ZERO: Vec3 = Vec3::new(0.0, 0.0, 0.0);
+    pub const X: Vec3 = Vec3::new(1.0, 0.0, 0.0);
+    pub const Y: Vec3 = Vec3::new(0.0, 1.0, 0.0);
+    pub const Z: Vec3 = Vec3::new(0.0, 0.0, 1.0);
+
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Vec3 { x, y, z }
+    }
+
+    pub fn dot(self, other: Vec3) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(self, other: Vec3) -> Vec3 {
+        Vec3::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// Returns the unit vector in the same direction, or zero for a zero vector.
+    pub fn normalize(self) -> Vec3 {
+        let length = self.length();
+        if length > 0.0 {
+            self * (1.0 / length)
+        } else {
+            Vec3::ZERO
+        }
+    }
+
+    pub fn lerp(self, other: Vec3, t: f32) -> Vec3 {
+        self + (other - self) * t
+    }
+
+    pub fn to_array(self) -> [f32; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+
+impl From<[f32; 3]> for Vec3 {
+    fn from([x, y, z]: [f32; 3]) -> Self {
+        Vec3::new(x, y, z)
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Vec3;
+
+    fn add(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl AddAssign for Vec3 {
+    fn add_assign(&mut self, other: Vec3) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Vec3;
+
+    fn sub(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl SubAssign for Vec3 {
+    fn sub_assign(&mut self, other: Vec3) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<f32> for Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, scale: f32) -> Vec3 {
+        Vec3::new(self.x * scale, self.y * scale, self.z * scale)
+    }
+}
+
+impl Neg for Vec3 {
+    type Output = Vec3;
+
+    fn neg(self) -> Vec3 {
+        Vec3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+/// A 4x4 matrix stored column-major, matching GLSL's `mat4` layout.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mat4 {
+    pub cols: [[f32; 4]; 4],
+}
+
+impl Mat4 {
+    pub const IDENTITY: Mat4 = Mat4 {
+        cols: [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+    };
+
+    pub fn translation(offset: Vec3) -> Mat4 {
+        let mut m = Mat4::IDENTITY;
+        m.cols[3] = [offset.x, offset.y, offset.z, 1.0];
+        m
+    }
+
+    pub fn scale(scale: Vec3) -> Mat4 {
+        let mut m = Mat4::IDENTITY;
+        m.cols[0][0] = scale.x;
+        m.cols[1][1] = scale.y;
+        m.cols[2][2] = scale.z;
+        m
+    }
+
+    /// Rotation by `angle` radians around +Y.
+    pub fn rotation_y(angle: f32) -> Mat4 {
+        let (sin, cos) = angle.sin_cos();
+        let mut m = Mat4::IDENTITY;
+        m.cols[0] = [cos, 0.0, -sin, 0.0];
+        m.cols[2] = [sin, 0.0, cos, 0.0];
+        m
+    }
+
+    /// Right-handed view matrix for an eye at `eye` looking along `forward`.
+    pub fn look_to(eye: Vec3, forward: Vec3, up: Vec3) -> Mat4 {
+        let f = forward.normalize();
+        let r = f.cross(up).normalize();
+        let u = r.cross(f);
+        Mat4 {
+            cols: [
+                [r.x, u.x, -f.x, 0.0],
+                [r.y, u.y, -f.y, 0.0],
+                [r.z, u.z, -f.z, 0.0],
+                [-r.dot(eye), -u.dot(eye), f.dot(eye), 1.0],
+            ],
+        }
+    }
+
+    /// Perspective projection for Vulkan clip space: the view looks down -Z,
+    /// depth maps to `[0, 1]` and +Y points down in NDC.
+    pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Mat4 {
+        let f = 1.0 / (fov_y / 2.0).tan();
+        Mat4 {
+            cols: [
+                [f / aspect, 0.0, 0.0, 0.0],
+                [0.0, -f, 0.0, 0.0],
+                [0.0, 0.0, far / (near - far), -1.0],
+                [0.0, 0.0, near * far / (near - far), 0.0],
+            ],
+        }
+    }
+
+    /// Transforms a point, returning homogeneous clip coordinates.
+    pub fn transform_point(&self, point: Vec3) -> [f32; 4] {
+        let v = [point.x, point.y, point.z, 1.0];
+        let mut out = [0.0; 4];
+        for (row, value) in out.iter_mut().enumerate() {
+            *value = (0..4).map(|col| self.cols[col][row] * v[col]).sum();
+        }
+        out
+    }
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Mat4::IDENTITY
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Mat4;
+
+    fn mul(self, other: Mat4) -> Mat4 {
+        let mut cols = [[0.0; 4]; 4];
+        for (col, out) in cols.iter_mut().enumerate() {
+            for (row, value) in out.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.cols[k][row] * other.cols[col][k]).sum();
+            }
+        }
+        Mat4 { cols }
+    }
+}
@@ -0,0 +1,367 @@
+//! Mesh data shared between the loaders and the GPU upload path.
+
+use crate::math::Vec3;
+use std::collections::HashMap;
+
+/// A vertex with a position and an RGBA color.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PosColor {
+    pub position: [f32; 3],
+    pub color: [f32; 4],
+}
+
+/// Vertices merged by `deduplicate`, with indices rebuilt to match.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Deduplicated {
+    pub vertices: Vec<PosColor>,
+    pub indices: Indices,
+    /// Vertex count before merging.
+    pub original_count: usize,
+}
+
+impl Deduplicated {
+    /// Fraction of the original vertices that remain, in `(0, 1]`.
+    pub fn ratio(&self) -> f32 {
+        if self.original_count == 0 {
+            return 1.0;
+        }
+        self.vertices.len() as f32 / self.original_count as f32
+    }
+}
+
+/// Merges vertices whose attributes agree within `tolerance` and returns
+/// the unique vertices with an index list reproducing the input order.
+///
+/// Attributes are quantized to multiples of `tolerance` and used as a hash
+/// key, so vertices on opposite sides of a quantization step stay separate.
+/// The first vertex seen for each key is kept unchanged.
+pub fn deduplicate(vertices: &[PosColor], tolerance: f32) -> Deduplicated {
+    let scale = 1.0 / tolerance.max(f32::EPSILON);
+    let quantize = |value: f32| (value * scale).round() as i64;
+
+    let mut unique = Vec::new();
+    let mut lookup: HashMap<[i64; 7], u32> = HashMap::with_capacity(vertices.len());
+    let indices = vertices
+        .iter()
+        .map(|vertex| {
+            let [x, y, z] = vertex.position;
+            let [r, g, b, a] = vertex.color;
+            let key = [x, y, z, r, g, b, a].map(quantize);
+            *lookup.entry(key).or_insert_with(|| {
+                unique.push(*vertex);
+                (unique.len() - 1) as u32
+            })
+        })
+        .collect();
+
+    let vertex_count = unique.len();
+    Deduplicated {
+        vertices: unique,
+        indices: Indices::new(indices, vertex_count),
+        original_count: vertices.len(),
+    }
+}
+
+/// Index value that ends the current strip when primitive restart is enabled.
+///
+/// `Indices::new` maps it onto the all-ones value of the narrower format.
+pub const PRIMITIVE_RESTART: u32 = u32::MAX;
+
+/// How a mesh's indices are assembled into triangles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Topology {
+    TriangleList,
+    /// Strips separated by `PRIMITIVE_RESTART`.
+    TriangleStrip,
+}
+
+/// Indexed geometry ready for upload.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mesh {
+    pub positions: Vec<Vec3>,
+    pub indices: Indices,
+    pub topology: Topology,
+}
+
+impl Mesh {
+    /// Builds a flat grid of `columns` by `rows` cells on the XZ plane,
+    /// centered on the origin with `spacing` between neighbouring vertices.
+    ///
+    /// Both topologies produce the same triangles with the same winding; the
+    /// strip form emits one strip per row of cells.
+    pub fn grid(columns: u32, rows: u32, spacing: f32, topology: Topology) -> Self {
+        let stride = columns + 1;
+        let offset_x = columns as f32 * spacing / 2.0;
+        let offset_z = rows as f32 * spacing / 2.0;
+        let positions: Vec<Vec3> = (0..=rows)
+            .flat_map(|z| {
+                (0..=columns).map(move |x| {
+                    Vec3::new(
+                        x as f32 * spacing - offset_x,
+                        0.0,
+                        z as f32 * spacing - offset_z,
+                    )
+                })
+            })
+            .collect();
+
+        let mut indices = Vec::new();
+        for z in 0..rows {
+            let near = z * stride;
+            let far = near + stride;
+            match topology {
+                Topology::TriangleList => {
+                    for x in 0..columns {
+                        indices.extend_from_slice(&[
+                            near + x,
+                            far + x,
+                            near + x + 1,
+                            near + x + 1,
+                            far + x,
+                            far + x + 1,
+                        ]);
+                    }
+                }
+                Topology::TriangleStrip => {
+                    if z > 0 {
+                        indices.push(PRIMITIVE_RESTART);
+                    }
+                    for x in 0..=columns {
+                        indices.push(near + x);
+                        indices.push(far + x);
+                    }
+                }
+            }
+        }
+
+        let vertex_count = positions.len();
+        Mesh {
+            positions,
+            indices: Indices::new(indices, vertex_count),
+            topology,
+        }
+    }
+}
+
+/// Width of the elements in an index buffer.
+///
+/// Maps one-to-one onto the index types a pipeline can bind.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IndexFormat {
+    U16,
+    U32,
+}
+
+impl IndexFormat {
+    /// Picks the narrowest format that can address `vertex_count` vertices.
+    ///
+    /// Meshes with up to 65,535 vertices only use indices `0..=65_534`, so
+    /// the all-ones value of each format stays free to serve as the
+    /// primitive restart index.
+    pub fn for_vertex_count(vertex_count: usize) -> Self {
+        if vertex_count <= u16::MAX as usize {
+            IndexFormat::U16
+        } else {
+            IndexFormat::U32
+        }
+    }
+
+    /// The primitive restart value for this format.
+    pub fn restart_index(self) -> u32 {
+        match self {
+            IndexFormat::U16 => u16::MAX as u32,
+            IndexFormat::U32 => u32::MAX,
+        }
+    }
+
+    /// Size of a single index in bytes.
+    pub fn size(self) -> usize {
+        match self {
+            IndexFormat::U16 => 2,
+            IndexFormat::U32 => 4,
+        }
+    }
+}
+
+/// Index data stored in the format chosen for its mesh.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Indices {
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+}
+
+impl Indices {
+    /// Stores `indices` as `u16` when `vertex_count` allows it, `u32` otherwise.
+    ///
+    /// `PRIMITIVE_RESTART` entries are preserved in either format.
+    pub fn new(indices: Vec<u32>, vertex_count: usize) -> Self {
+        debug_assert!(indices
+            .iter()
+            .all(|&i| i == PRIMITIVE_RESTART || (i as usize) < vertex_count));
+        match IndexFormat::for_vertex_count(vertex_count) {
+            IndexFormat::U16 => Indices::U16(indices.into_iter().map(|i| i as u16).collect()),
+            IndexFormat::U32 => Indices::U32(indices),
+        }
+    }
+
+    pub fn format(&self) -> IndexFormat {
+        match self {
+            Indices::U16(_) => IndexFormat::U16,
+            Indices::U32(_) => IndexFormat::U32,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Indices::U16(indices) => indices.len(),
+            Indices::U32(indices) => indices.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the indices as native-endian bytes, ready for a buffer upload.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Indices::U16(indices) => indices
+                .iter()
+                .flat_map(|i| i.to_ne_bytes().to_vec())
+                .collect(),
+            Indices::U32(indices) => indices
+                .iter()
+                .flat_map(|i| i.to_ne_bytes().to_vec())
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_format_reserves_the_restart_value() {
+        assert_eq!(IndexFormat::for_vertex_count(3), IndexFormat::U16);
+        assert_eq!(IndexFormat::for_vertex_count(65_535), IndexFormat::U16);
+        assert_eq!(IndexFormat::for_vertex_count(65_536), IndexFormat::U32);
+        assert_eq!(IndexFormat::for_vertex_count(100_000), IndexFormat::U32);
+    }
+
+    #[test]
+    fn indices_pick_their_format_from_the_vertex_count() {
+        let small = Indices::new(vec![0, 1, 2], 3);
+        assert_eq!(small.format(), IndexFormat::U16);
+        assert_eq!(small, Indices::U16(vec![0, 1, 2]));
+
+        let large = Indices::new(vec![0, 50_000, 99_999], 100_000);
+        assert_eq!(large.format(), IndexFormat::U32);
+        assert_eq!(large, Indices::U32(vec![0, 50_000, 99_999]));
+    }
+
+    fn widened(indices: &Indices) -> Vec<u32> {
+        let restart = indices.format().restart_index();
+        let widen = |i: u32| if i == restart { PRIMITIVE_RESTART } else { i };
+        match indices {
+            Indices::U16(indices) => indices.iter().map(|&i| widen(i as u32)).collect(),
+            Indices::U32(indices) => indices.iter().map(|&i| widen(i)).collect(),
+        }
+    }
+
+    /// Expands indices into triangles the way the input assembler does.
+    fn triangles(mesh: &Mesh) -> Vec<[u32; 3]> {
+        let indices = widened(&mesh.indices);
+        match mesh.topology {
+            Topology::TriangleList => indices.chunks(3).map(|t| [t[0], t[1], t[2]]).collect(),
+            Topology::TriangleStrip => indices
+                .split(|&i| i == PRIMITIVE_RESTART)
+                .flat_map(|strip| {
+                    strip.windows(3).enumerate().map(|(k, t)| {
+                        if k % 2 == 0 {
+                            [t[0], t[1], t[2]]
+                        } else {
+                            [t[1], t[0], t[2]]
+                        }
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn strip_grid_matches_list_grid() {
+        let list = Mesh::grid(4, 3, 0.5, Topology::TriangleList);
+        let strip = Mesh::grid(4, 3, 0.5, Topology::TriangleStrip);
+        assert_eq!(list.positions, strip.positions);
+        assert_eq!(triangles(&list).len(), 4 * 3 * 2);
+        assert_eq!(triangles(&strip), triangles(&list));
+    }
+
+    #[test]
+    fn strip_restart_narrows_to_u16_all_ones() {
+        let strip = Mesh::grid(2, 2, 1.0, Topology::TriangleStrip);
+        match &strip.indices {
+            Indices::U16(indices) => {
+                assert_eq!(indices.iter().filter(|&&i| i == 0xFFFF).count(), 1);
+            }
+            other => panic!("expected u16 indices, got {:?}", other.format()),
+        }
+    }
+
+    const CUBE_OBJ: &str = "\
+v -1 -1 -1
+v 1 -1 -1
+v 1 1 -1
+v -1 1 -1
+v -1 -1 1
+v 1 -1 1
+v 1 1 1
+v -1 1 1
+f 1 2 3 4
+f 5 8 7 6
+f 1 5 6 2
+f 2 6 7 3
+f 3 7 8 4
+f 5 1 4 8
+";
+
+    #[test]
+    fn deduplicate_reproduces_the_input() {
+        let vertices = crate::mesh_loader::parse_obj(CUBE_OBJ.as_bytes()).unwrap();
+        assert_eq!(vertices.len(), 36);
+
+        let merged = deduplicate(&vertices, 1e-4);
+        assert_eq!(merged.vertices.len(), 8);
+        assert_eq!(merged.original_count, 36);
+        assert!((merged.ratio() - 8.0 / 36.0).abs() < 1e-6);
+
+        let rebuilt: Vec<PosColor> = widened(&merged.indices)
+            .into_iter()
+            .map(|i| merged.vertices[i as usize])
+            .collect();
+        assert_eq!(rebuilt, vertices);
+    }
+
+    #[test]
+    fn deduplicate_merges_within_tolerance_only() {
+        let vertex = |x: f32| PosColor {
+            position: [x, 0.0, 0.0],
+            color: [1.0; 4],
+        };
+        let merged = deduplicate(&[vertex(0.0), vertex(0.00001), vertex(0.5)], 1e-3);
+        assert_eq!(merged.vertices, vec![vertex(0.0), vertex(0.5)]);
+        assert_eq!(merged.indices, Indices::U16(vec![0, 0, 1]));
+    }
+
+    #[test]
+    fn to_bytes_matches_len_times_size() {
+        for &vertex_count in &[3, 100_000] {
+            let indices = Indices::new(vec![0, 1, 2, 2, 1, 0], vertex_count);
+            assert_eq!(
+                indices.to_bytes().len(),
+                indices.len() * indices.format().size()
+            );
+        }
+    }
+}
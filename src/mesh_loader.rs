@@ -0,0 +1,122 @@
+//! Loading vertex data from model files.
+
+use crate::mesh::{self, Deduplicated, PosColor};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+const DEFAULT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// A line that could not be understood, with its 1-based number.
+    Parse {
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "failed to read mesh: {}", err),
+            Error::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+/// Loads a Wavefront OBJ file as a triangle list.
+///
+/// Only `v` and `f` lines are used; normals, texture coordinates, groups and
+/// materials are skipped. Vertices may carry the common `v x y z r g b [a]`
+/// color extension and default to white otherwise. Faces with more than three
+/// vertices are triangulated as fans, and negative (relative) indices are
+/// supported.
+pub fn load_obj(path: &Path) -> Result<Vec<PosColor>, Error> {
+    parse_obj(BufReader::new(File::open(path)?))
+}
+
+/// Loads an OBJ file like `load_obj` and merges duplicate vertices into an
+/// indexed mesh; see `mesh::deduplicate` for how `tolerance` is applied.
+pub fn load_obj_indexed(path: &Path, tolerance: f32) -> Result<Deduplicated, Error> {
+    Ok(mesh::deduplicate(&load_obj(path)?, tolerance))
+}
+
+/// Parses OBJ data from any reader; see `load_obj`.
+pub fn parse_obj<R: BufRead>(reader: R) -> Result<Vec<PosColor>, Error> {
+    let mut positions: Vec<PosColor> = Vec::new();
+    let mut vertices = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let number = index + 1;
+        let error = |message: String| Error::Parse {
+            line: number,
+            message,
+        };
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("v") => {
+                let values = tokens
+                    .map(|token| token.parse::<f32>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|err| error(format!("invalid vertex component: {}", err)))?;
+                let color = match values.len() {
+                    // The optional fourth value is the homogeneous `w`.
+                    3 | 4 => DEFAULT_COLOR,
+                    6 => [values[3], values[4], values[5], 1.0],
+                    7 => [values[3], values[4], values[5], values[6]],
+                    count => return Err(error(format!("vertex has {} components", count))),
+                };
+                positions.push(PosColor {
+                    position: [values[0], values[1], values[2]],
+                    color,
+                });
+            }
+            Some("f") => {
+                let face = tokens
+                    .map(|token| resolve_index(token, positions.len()))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(error)?;
+                if face.len() < 3 {
+                    return Err(error(format!("face has {} vertices", face.len())));
+                }
+                for i in 1..face.len() - 1 {
+                    vertices.push(positions[face[0]]);
+                    vertices.push(positions[face[i]]);
+                    vertices.push(positions[face[i + 1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(vertices)
+}
+
+/// Turns the position part of a face token such as `3`, `3/1`, `3//2` or
+/// `-1` into a zero-based index into the vertices read so far.
+fn resolve_index(token: &str, count: usize) -> Result<usize, String> {
+    let position = token.split('/').next().unwrap_or(token);
+    let index: i64 = position
+        .parse()
+        .map_err(|_| format!("invalid face index `{}`", token))?;
+    let resolved = if index < 0 {
+        count as i64 + index
+    } else {
+        index - 1
+    };
+    if index == 0 || resolved < 0 || resolved >= count as i64 {
+        return Err(format!("face index {} out of range", index));
+    }
+    Ok(resolved as usize)
+}
@@ -0,0 +1,112 @@
+//! Rolling statistics over per-pass GPU timings.
+//!
+//! Raw timestamp deltas are noisy from frame to frame, so each pass keeps a
+//! window of recent samples and reports summary figures over it.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimingSummary {
+    pub average: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    pub p95: Duration,
+    pub samples: usize,
+}
+
+#[derive(Debug)]
+pub struct PassTimings {
+    window: usize,
+    passes: HashMap<String, VecDeque<Duration>>,
+}
+
+impl PassTimings {
+    /// Keeps the last `window` samples of each pass.
+    pub fn new(window: usize) -> Self {
+        PassTimings {
+            window: window.max(1),
+            passes: HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, pass: &str, time: Duration) {
+        let window = self.window;
+        let samples = self
+            .passes
+            .entry(pass.to_owned())
+            .or_insert_with(|| VecDeque::with_capacity(window));
+        if samples.len() == window {
+            samples.pop_front();
+        }
+        samples.push_back(time);
+    }
+
+    pub fn summary(&self, pass: &str) -> Option<TimingSummary> {
+        let samples = self.passes.get(pass).filter(|s| !s.is_empty())?;
+        let mut sorted: Vec<Duration> = samples.iter().copied().collect();
+        sorted.sort();
+        let total: Duration = sorted.iter().sum();
+        let p95_index = (sorted.len() * 95).div_ceil(100) - 1;
+        Some(TimingSummary {
+            average: total / sorted.len() as u32,
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            p95: sorted[p95_index],
+            samples: sorted.len(),
+        })
+    }
+
+    /// Summaries for every recorded pass, sorted by name for display.
+    pub fn summaries(&self) -> Vec<(&str, TimingSummary)> {
+        let mut summaries: Vec<_> = self
+            .passes
+            .keys()
+            .filter_map(|pass| Some((pass.as_str(), self.summary(pass)?)))
+            .collect();
+        summaries.sort_by(|a, b| a.0.cmp(b.0));
+        summaries
+    }
+
+    /// Discards all samples, for use after resolution or settings changes
+    /// that make older timings meaningless.
+    pub fn reset(&mut self) {
+        self.passes.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn summary_covers_only_the_window() {
+        let mut timings = PassTimings::new(20);
+        for millis in 1..=25 {
+            timings.record("shadow", ms(millis));
+        }
+        timings.record("tonemap", ms(1));
+
+        // Samples 1..=5 have been pushed out, leaving 6..=25.
+        assert_eq!(
+            timings.summary("shadow"),
+            Some(TimingSummary {
+                average: Duration::from_micros(15_500),
+                min: ms(6),
+                max: ms(25),
+                p95: ms(24),
+                samples: 20,
+            })
+        );
+        let names: Vec<&str> = timings.summaries().iter().map(|s| s.0).collect();
+        assert_eq!(names, ["shadow", "tonemap"]);
+
+        timings.reset();
+        assert_eq!(timings.summary("shadow"), None);
+        assert!(timings.summaries().is_empty());
+    }
+}
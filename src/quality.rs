@@ -0,0 +1,231 @@
+//! Automatic quality scaling to hold a target frame time.
+
+use std::fmt;
+use std::time::Duration;
+
+/// The settings the controller is allowed to change.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QualitySettings {
+    pub resolution_scale: f32,
+    pub shadow_resolution: u32,
+    pub post_effects: bool,
+}
+
+/// Presets from cheapest to most expensive.
+pub fn default_levels() -> Vec<QualitySettings> {
+    vec![
+        QualitySettings {
+            resolution_scale: 0.5,
+            shadow_resolution: 512,
+            post_effects: false,
+        },
+        QualitySettings {
+            resolution_scale: 0.75,
+            shadow_resolution: 1024,
+            post_effects: false,
+        },
+        QualitySettings {
+            resolution_scale: 1.0,
+            shadow_resolution: 1024,
+            post_effects: true,
+        },
+        QualitySettings {
+            resolution_scale: 1.0,
+            shadow_resolution: 2048,
+            post_effects: true,
+        },
+    ]
+}
+
+/// A change of quality level made by the controller.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QualityChange {
+    pub from: usize,
+    pub to: usize,
+    pub settings: QualitySettings,
+    /// The averaged frame time that triggered the change.
+    pub frame_time: Duration,
+}
+
+impl fmt::Display for QualityChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let direction = if self.to < self.from {
+            "lowered"
+        } else {
+            "raised"
+        };
+        write!(
+            f,
+            "quality {} from level {} to {} at {:.2} ms average frame time \
+             (resolution scale {}, shadow map {}, post effects {})",
+            direction,
+            self.from,
+            self.to,
+            self.frame_time.as_secs_f64() * 1000.0,
+            self.settings.resolution_scale,
+            self.settings.shadow_resolution,
+            if self.settings.post_effects {
+                "on"
+            } else {
+                "off"
+            },
+        )
+    }
+}
+
+/// Steps through quality levels based on measured GPU frame times.
+///
+/// Frame times are averaged over `window` frames. The level drops when the
+/// average exceeds the target by more than `hysteresis` (a fraction of the
+/// target) and rises when it is under the target by more than that margin.
+/// After each change the window restarts, so the next decision is based only
+/// on frames rendered at the new level. Besides the dead band between the two
+/// thresholds, stepping back up is held off for `retry_windows` windows after
+/// a drop, so a level that is just too expensive is not retried every window.
+///
+/// The controller does not log anything itself; callers should log each
+/// returned `QualityChange`, whose `Display` output is meant for that.
+#[derive(Clone, Debug)]
+pub struct AutoQuality {
+    pub target: Duration,
+    pub hysteresis: f32,
+    pub window: u32,
+    pub retry_windows: u32,
+    levels: Vec<QualitySettings>,
+    current: usize,
+    total: Duration,
+    samples: u32,
+    hold_off: u32,
+}
+
+impl AutoQuality {
+    /// Aims for frames of `target` length, starting at the highest level of
+    /// `levels`, which must not be empty.
+    pub fn new(target: Duration, levels: Vec<QualitySettings>) -> Self {
+        assert!(!levels.is_empty(), "auto quality needs at least one level");
+        AutoQuality {
+            target,
+            hysteresis: 0.15,
+            window: 60,
+            retry_windows: 5,
+            current: levels.len() - 1,
+            levels,
+            total: Duration::from_secs(0),
+            samples: 0,
+            hold_off: 0,
+        }
+    }
+
+    pub fn level(&self) -> usize {
+        self.current
+    }
+
+    pub fn settings(&self) -> QualitySettings {
+        self.levels[self.current]
+    }
+
+    /// Feeds one frame's time and returns a change once one is warranted.
+    pub fn update(&mut self, frame_time: Duration) -> Option<QualityChange> {
+        self.total += frame_time;
+        self.samples += 1;
+        if self.samples < self.window.max(1) {
+            return None;
+        }
+
+        let average = self.total / self.samples;
+        self.reset();
+        let held_off = self.hold_off > 0;
+        self.hold_off = self.hold_off.saturating_sub(1);
+        let target = self.target.as_secs_f32();
+        let average_secs = average.as_secs_f32();
+        let next = if average_secs > target * (1.0 + self.hysteresis) && self.current > 0 {
+            self.hold_off = self.retry_windows;
+            self.current - 1
+        } else if average_secs < target * (1.0 - self.hysteresis)
+            && self.current + 1 < self.levels.len()
+            && !held_off
+        {
+            self.current + 1
+        } else {
+            return None;
+        };
+
+        let change = QualityChange {
+            from: self.current,
+            to: next,
+            settings: self.levels[next],
+            frame_time: average,
+        };
+        self.current = next;
+        Some(change)
+    }
+
+    /// Discards the frames averaged so far.
+    pub fn reset(&mut self) {
+        self.total = Duration::from_secs(0);
+        self.samples = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGET: Duration = Duration::from_millis(16);
+
+    fn controller() -> AutoQuality {
+        let mut quality = AutoQuality::new(TARGET, default_levels());
+        quality.window = 4;
+        quality
+    }
+
+    /// Runs `windows` full windows where each level renders in
+    /// `cost[level] * TARGET`, returning the level after every window.
+    fn simulate(quality: &mut AutoQuality, cost: &[f32], windows: usize) -> Vec<usize> {
+        (0..windows)
+            .map(|_| {
+                for _ in 0..quality.window {
+                    quality.update(TARGET.mul_f32(cost[quality.level()]));
+                }
+                quality.level()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn holds_level_inside_the_dead_band() {
+        let mut quality = controller();
+        let levels = simulate(&mut quality, &[0.5, 0.7, 0.9, 1.1], 20);
+        assert!(levels.iter().all(|&level| level == 3));
+    }
+
+    #[test]
+    fn drops_until_under_target_then_settles() {
+        let mut quality = controller();
+        let levels = simulate(&mut quality, &[0.5, 0.95, 1.3, 2.0], 20);
+        assert_eq!(&levels[..2], &[2, 1]);
+        assert!(levels[2..].iter().all(|&level| level == 1));
+    }
+
+    #[test]
+    fn retry_hold_off_delays_stepping_back_up() {
+        let mut quality = controller();
+        quality.retry_windows = 3;
+        // Level 3 is too slow and level 2 is fast enough to tempt a retry.
+        let levels = simulate(&mut quality, &[0.5, 0.5, 0.5, 1.3], 9);
+        assert_eq!(levels, vec![2, 2, 2, 2, 3, 2, 2, 2, 2]);
+    }
+
+    #[test]
+    fn reports_each_change() {
+        let mut quality = controller();
+        let changes: Vec<QualityChange> =
+            (0..4).filter_map(|_| quality.update(TARGET * 2)).collect();
+        assert_eq!(changes.len(), 1);
+        assert_eq!((changes[0].from, changes[0].to), (3, 2));
+        assert_eq!(changes[0].settings, default_levels()[2]);
+        assert!(changes[0]
+            .to_string()
+            .starts_with("quality lowered from level 3 to 2"));
+    }
+}
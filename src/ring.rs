@@ -0,0 +1,229 @@
+//! Ring sub-allocation for per-frame data in a persistently mapped buffer.
+//!
+//! The allocator only hands out offsets; the caller owns the mapped memory.
+//! Each frame's allocations stay reserved until the caller reports that the
+//! frame's fence has signaled, so wrapping around never overwrites a region
+//! the GPU may still be reading.
+
+use std::collections::VecDeque;
+
+/// Space consumed by one submitted frame.
+#[derive(Clone, Copy, Debug)]
+struct FrameRegion {
+    frame: u64,
+    end: u64,
+    bytes: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RingStats {
+    pub capacity: u64,
+    pub used: u64,
+    pub free: u64,
+}
+
+#[derive(Debug)]
+pub struct RingBuffer {
+    capacity: u64,
+    head: u64,
+    tail: u64,
+    used: u64,
+    frame_bytes: u64,
+    frames: VecDeque<FrameRegion>,
+}
+
+impl RingBuffer {
+    pub fn new(capacity: u64) -> Self {
+        RingBuffer {
+            capacity,
+            head: 0,
+            tail: 0,
+            used: 0,
+            frame_bytes: 0,
+            frames: VecDeque::new(),
+        }
+    }
+
+    /// Reserves `size` bytes aligned to `align` for the current frame and
+    /// returns their offset, or `None` if the in-flight frames leave no room.
+    ///
+    /// An allocation never straddles the end of the buffer; the unused tail
+    /// is skipped and stays reserved until the frame is released.
+    pub fn allocate(&mut self, size: u64, align: u64) -> Option<u64> {
+        debug_assert!(align.is_power_of_two());
+        if self.used == 0 {
+            self.head = 0;
+            self.tail = 0;
+        }
+
+        let aligned = align_up(self.head, align);
+        let wrapped = self.used > 0 && self.head <= self.tail;
+        let (offset, skip_tail) = if wrapped {
+            if aligned.checked_add(size)? > self.tail {
+                return None;
+            }
+            (aligned, false)
+        } else if aligned.checked_add(size)? <= self.capacity {
+            (aligned, false)
+        } else if size <= self.tail {
+            (0, true)
+        } else {
+            return None;
+        };
+
+        let end = offset + size;
+        let consumed = if skip_tail {
+            self.capacity - self.head + end
+        } else {
+            end - self.head
+        };
+        self.head = end;
+        self.used += consumed;
+        self.frame_bytes += consumed;
+        Some(offset)
+    }
+
+    /// Closes the current frame's allocations under the given frame index.
+    pub fn end_frame(&mut self, frame: u64) {
+        if self.frame_bytes > 0 {
+            self.frames.push_back(FrameRegion {
+                frame,
+                end: self.head,
+                bytes: self.frame_bytes,
+            });
+            self.frame_bytes = 0;
+        }
+    }
+
+    /// Frees every frame up to and including `completed`, whose fence the
+    /// caller has observed as signaled.
+    pub fn release(&mut self, completed: u64) {
+        while let Some(region) = self.frames.front() {
+            if region.frame > completed {
+                break;
+            }
+            self.tail = region.end;
+            self.used -= region.bytes;
+            self.frames.pop_front();
+        }
+    }
+
+    pub fn stats(&self) -> RingStats {
+        RingStats {
+            capacity: self.capacity,
+            used: self.used,
+            free: self.capacity - self.used,
+        }
+    }
+}
+
+fn align_up(value: u64, align: u64) -> u64 {
+    (value + align - 1) & !(align - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_to_zero_and_reserves_the_skipped_tail() {
+        let mut ring = RingBuffer::new(256);
+        assert_eq!(ring.allocate(100, 4), Some(0));
+        ring.end_frame(0);
+        assert_eq!(ring.allocate(100, 4), Some(100));
+        ring.end_frame(1);
+        ring.release(0);
+
+        // 56 bytes remain at the end, too few for 100, so the allocation
+        // wraps into the space frame 0 freed and the tail stays reserved.
+        assert_eq!(ring.allocate(100, 4), Some(0));
+        assert_eq!(
+            ring.stats(),
+            RingStats {
+                capacity: 256,
+                used: 256,
+                free: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn returns_none_while_in_flight_frames_fill_the_ring() {
+        let mut ring = RingBuffer::new(256);
+        assert_eq!(ring.allocate(128, 4), Some(0));
+        ring.end_frame(0);
+        assert_eq!(ring.allocate(128, 4), Some(128));
+        ring.end_frame(1);
+        assert_eq!(ring.allocate(1, 1), None);
+
+        ring.release(0);
+        assert_eq!(ring.allocate(128, 4), Some(0));
+        assert_eq!(ring.allocate(1, 1), None);
+    }
+
+    #[test]
+    fn release_frees_frames_up_to_the_completed_one() {
+        let mut ring = RingBuffer::new(1024);
+        for frame in 0..4 {
+            ring.allocate(64, 16).unwrap();
+            ring.end_frame(frame);
+        }
+        assert_eq!(ring.stats().used, 256);
+
+        ring.release(1);
+        assert_eq!(ring.stats().used, 128);
+        ring.release(1);
+        assert_eq!(ring.stats().used, 128);
+        ring.release(3);
+        assert_eq!(
+            ring.stats(),
+            RingStats {
+                capacity: 1024,
+                used: 0,
+                free: 1024,
+            }
+        );
+    }
+
+    #[test]
+    fn allocations_respect_alignment() {
+        let mut ring = RingBuffer::new(1024);
+        assert_eq!(ring.allocate(3, 1), Some(0));
+        assert_eq!(ring.allocate(8, 256), Some(256));
+        // Alignment padding counts as used space.
+        assert_eq!(ring.stats().used, 264);
+    }
+
+    #[test]
+    fn live_allocations_never_overlap_across_wraps() {
+        let mut ring = RingBuffer::new(1024);
+        let mut live: Vec<(u64, u64, u64)> = Vec::new();
+        let mut seed = 1u64;
+        let frames_in_flight = 2;
+
+        for frame in 0..2000u64 {
+            for _ in 0..3 {
+                seed = seed
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                let size = 16 + (seed >> 33) % 200;
+                if let Some(offset) = ring.allocate(size, 16) {
+                    assert!(offset + size <= 1024);
+                    for &(_, other, other_size) in &live {
+                        assert!(offset + size <= other || other + other_size <= offset);
+                    }
+                    live.push((frame, offset, size));
+                }
+            }
+            ring.end_frame(frame);
+            if frame >= frames_in_flight {
+                let completed = frame - frames_in_flight;
+                ring.release(completed);
+                live.retain(|&(f, _, _)| f > completed);
+            }
+        }
+
+        ring.release(u64::MAX);
+        assert_eq!(ring.stats().used, 0);
+    }
+}
@@ -0,0 +1,238 @@
+//! Scene hierarchy with parent-relative transforms.
+
+use crate::math::Mat4;
+use std::fmt;
+use std::mem;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EntityId(usize);
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SceneError {
+    /// Parenting would make an entity its own ancestor.
+    Cycle { child: EntityId, parent: EntityId },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Cycle { child, parent } => write!(
+                f,
+                "parenting entity {} to {} would create a cycle",
+                child.0, parent.0
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+#[derive(Clone, Debug)]
+struct Node {
+    local: Mat4,
+    world: Mat4,
+    parent: Option<EntityId>,
+    children: Vec<EntityId>,
+    dirty: bool,
+}
+
+/// Entities whose world transform is their local transform applied on top
+/// of their parent's world transform.
+///
+/// Changing a local transform or a parent only marks that entity dirty;
+/// `update` then walks down from the dirty entities alone, recomputing them
+/// and their descendants without visiting untouched subtrees.
+#[derive(Clone, Debug, Default)]
+pub struct SceneGraph {
+    nodes: Vec<Node>,
+    dirty: Vec<EntityId>,
+}
+
+impl SceneGraph {
+    pub fn new() -> Self {
+        SceneGraph::default()
+    }
+
+    /// Adds a root entity with the given local transform.
+    pub fn spawn(&mut self, local: Mat4) -> EntityId {
+        self.nodes.push(Node {
+            local,
+            world: local,
+            parent: None,
+            children: Vec::new(),
+            dirty: false,
+        });
+        let id = EntityId(self.nodes.len() - 1);
+        self.mark_dirty(id);
+        id
+    }
+
+    pub fn local(&self, id: EntityId) -> Mat4 {
+        self.nodes[id.0].local
+    }
+
+    pub fn set_local(&mut self, id: EntityId, local: Mat4) {
+        self.nodes[id.0].local = local;
+        self.mark_dirty(id);
+    }
+
+    pub fn parent(&self, id: EntityId) -> Option<EntityId> {
+        self.nodes[id.0].parent
+    }
+
+    pub fn children(&self, id: EntityId) -> &[EntityId] {
+        &self.nodes[id.0].children
+    }
+
+    /// Reparents `child` under `parent`, or makes it a root for `None`.
+    /// The local transform is kept, so the entity moves with its new parent.
+    pub fn set_parent(
+        &mut self,
+        child: EntityId,
+        parent: Option<EntityId>,
+    ) -> Result<(), SceneError> {
+        if let Some(parent) = parent {
+            let mut ancestor = Some(parent);
+            while let Some(id) = ancestor {
+                if id == child {
+                    return Err(SceneError::Cycle { child, parent });
+                }
+                ancestor = self.nodes[id.0].parent;
+            }
+        }
+
+        if let Some(old) = self.nodes[child.0].parent {
+            self.nodes[old.0].children.retain(|&c| c != child);
+        }
+        if let Some(parent) = parent {
+            self.nodes[parent.0].children.push(child);
+        }
+        self.nodes[child.0].parent = parent;
+        self.mark_dirty(child);
+        Ok(())
+    }
+
+    /// World transform as of the last `update`.
+    pub fn world(&self, id: EntityId) -> Mat4 {
+        self.nodes[id.0].world
+    }
+
+    /// Recomputes the world transforms of dirty entities and everything
+    /// below them, returning how many transforms were recomputed. Call once
+    /// per frame before rendering.
+    pub fn update(&mut self) -> usize {
+        let mut dirty = mem::take(&mut self.dirty);
+        // Shallowest first, so a parent is always current before its
+        // children read its world transform.
+        dirty.sort_by_cached_key(|&id| self.depth(id));
+
+        let mut recomputed = 0;
+        let mut stack = Vec::new();
+        for root in dirty {
+            // Already recomputed as part of a dirty ancestor's subtree.
+            if !self.nodes[root.0].dirty {
+                continue;
+            }
+            stack.push(root);
+            while let Some(id) = stack.pop() {
+                let parent_world = self.nodes[id.0]
+                    .parent
+                    .map_or(Mat4::IDENTITY, |parent| self.nodes[parent.0].world);
+                let node = &mut self.nodes[id.0];
+                node.world = parent_world * node.local;
+                node.dirty = false;
+                recomputed += 1;
+                stack.extend_from_slice(&node.children);
+            }
+        }
+        recomputed
+    }
+
+    fn mark_dirty(&mut self, id: EntityId) {
+        let node = &mut self.nodes[id.0];
+        if !node.dirty {
+            node.dirty = true;
+            self.dirty.push(id);
+        }
+    }
+
+    fn depth(&self, id: EntityId) -> usize {
+        let mut depth = 0;
+        let mut ancestor = self.nodes[id.0].parent;
+        while let Some(parent) = ancestor {
+            depth += 1;
+            ancestor = self.nodes[parent.0].parent;
+        }
+        depth
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Vec3;
+
+    fn origin_of(scene: &SceneGraph, id: EntityId) -> Vec3 {
+        let [x, y, z, _] = scene.world(id).transform_point(Vec3::ZERO);
+        Vec3::new(x, y, z)
+    }
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-5, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn child_follows_its_parent() {
+        let mut scene = SceneGraph::new();
+        let tank = scene.spawn(Mat4::translation(Vec3::new(10.0, 0.0, 0.0)));
+        let turret = scene.spawn(Mat4::translation(Vec3::new(1.0, 0.0, 0.0)));
+        scene.set_parent(turret, Some(tank)).unwrap();
+        scene.update();
+        assert_near(origin_of(&scene, turret), Vec3::new(11.0, 0.0, 0.0));
+
+        let turn = Mat4::rotation_y(std::f32::consts::FRAC_PI_2);
+        scene.set_local(tank, Mat4::translation(Vec3::new(10.0, 0.0, 0.0)) * turn);
+        scene.update();
+        assert_near(origin_of(&scene, turret), Vec3::new(10.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn update_only_visits_dirty_subtrees() {
+        let mut scene = SceneGraph::new();
+        let a = scene.spawn(Mat4::IDENTITY);
+        let a_child = scene.spawn(Mat4::IDENTITY);
+        let b = scene.spawn(Mat4::IDENTITY);
+        let b_child = scene.spawn(Mat4::IDENTITY);
+        scene.set_parent(a_child, Some(a)).unwrap();
+        scene.set_parent(b_child, Some(b)).unwrap();
+        assert_eq!(scene.update(), 4);
+        assert_eq!(scene.update(), 0);
+
+        scene.set_local(a, Mat4::translation(Vec3::X));
+        scene.set_local(a_child, Mat4::translation(Vec3::Y));
+        assert_eq!(scene.update(), 2);
+        assert_near(origin_of(&scene, a_child), Vec3::new(1.0, 1.0, 0.0));
+        assert_near(origin_of(&scene, b_child), Vec3::ZERO);
+    }
+
+    #[test]
+    fn set_parent_rejects_cycles() {
+        let mut scene = SceneGraph::new();
+        let a = scene.spawn(Mat4::IDENTITY);
+        let b = scene.spawn(Mat4::IDENTITY);
+        let c = scene.spawn(Mat4::IDENTITY);
+        scene.set_parent(b, Some(a)).unwrap();
+        scene.set_parent(c, Some(b)).unwrap();
+
+        assert_eq!(
+            scene.set_parent(a, Some(c)),
+            Err(SceneError::Cycle {
+                child: a,
+                parent: c,
+            })
+        );
+        assert!(scene.set_parent(a, Some(a)).is_err());
+        assert_eq!(scene.parent(a), None);
+        assert_eq!(scene.children(c), &[] as &[EntityId]);
+    }
+}
@@ -0,0 +1,133 @@
+//! Locating and reading shader sources at runtime.
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShaderStage {
+    Vertex,
+    Fragment,
+    Compute,
+}
+
+impl ShaderStage {
+    /// Infers the stage from conventional extensions (`.vert`, `.frag`,
+    /// `.comp`) or from `vert`/`frag`/`comp` in the file stem, as in
+    /// `vert.glsl`.
+    pub fn from_path(path: &Path) -> Option<ShaderStage> {
+        let matches = |name: &str| match name {
+            "vert" => Some(ShaderStage::Vertex),
+            "frag" => Some(ShaderStage::Fragment),
+            "comp" => Some(ShaderStage::Compute),
+            _ => None,
+        };
+        let extension = path.extension().and_then(|e| e.to_str());
+        let stem = path.file_stem().and_then(|s| s.to_str());
+        extension
+            .and_then(matches)
+            .or_else(|| stem.and_then(matches))
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// No file at the path, checked against each candidate location.
+    NotFound {
+        path: PathBuf,
+        searched: Vec<PathBuf>,
+    },
+    /// The stage could not be inferred from the file name.
+    UnknownStage(PathBuf),
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NotFound { path, searched } => {
+                write!(f, "shader {} not found (searched", path.display())?;
+                for candidate in searched {
+                    write!(f, " {}", candidate.display())?;
+                }
+                write!(f, ")")
+            }
+            Error::UnknownStage(path) => {
+                write!(f, "cannot tell the shader stage of {}", path.display())
+            }
+            Error::Io { path, source } => {
+                write!(f, "failed to read shader {}: {}", path.display(), source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// GLSL source read from disk, ready to be compiled for its stage.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShaderSource {
+    pub path: PathBuf,
+    pub stage: ShaderStage,
+    pub source: String,
+}
+
+impl ShaderSource {
+    /// Reads the shader at `path`, inferring its stage from the file name.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let stage =
+            ShaderStage::from_path(path).ok_or_else(|| Error::UnknownStage(path.to_owned()))?;
+        ShaderSource::load_stage(path, stage)
+    }
+
+    /// Reads the shader at `path` as the given stage.
+    ///
+    /// Absolute paths are used as is. Relative paths are looked up next to
+    /// the executable first, so shaders shipped alongside it are found
+    /// whatever the working directory, and then against the working
+    /// directory.
+    pub fn load_stage<P: AsRef<Path>>(path: P, stage: ShaderStage) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let searched = candidates(path);
+        let resolved = searched
+            .iter()
+            .find(|candidate| candidate.is_file())
+            .cloned()
+            .ok_or_else(|| Error::NotFound {
+                path: path.to_owned(),
+                searched: searched.clone(),
+            })?;
+        let source = fs::read_to_string(&resolved).map_err(|source| Error::Io {
+            path: resolved.clone(),
+            source,
+        })?;
+        Ok(ShaderSource {
+            path: resolved,
+            stage,
+            source,
+        })
+    }
+}
+
+fn candidates(path: &Path) -> Vec<PathBuf> {
+    if path.is_absolute() {
+        return vec![path.to_owned()];
+    }
+    let mut candidates = Vec::new();
+    if let Some(dir) = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_owned))
+    {
+        candidates.push(dir.join(path));
+    }
+    if let Ok(dir) = env::current_dir() {
+        candidates.push(dir.join(path));
+    }
+    candidates
+}
@@ -0,0 +1,143 @@
+//! Frame timing passed to pipelines each frame.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Timing for the frame being rendered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameContext {
+    /// Time since the clock started, at the start of this frame.
+    pub total: Duration,
+    /// Time since the previous frame started; zero on the first frame.
+    pub delta: Duration,
+    /// Number of frames before this one.
+    pub frame: u64,
+}
+
+/// Produces a `FrameContext` per loop iteration from a monotonic clock.
+#[derive(Clone, Copy, Debug)]
+pub struct FrameClock {
+    start: Instant,
+    last: Instant,
+    frame: u64,
+}
+
+impl FrameClock {
+    pub fn new() -> Self {
+        let now = Instant::now();
+        FrameClock {
+            start: now,
+            last: now,
+            frame: 0,
+        }
+    }
+
+    /// Starts a new frame and returns its timing.
+    pub fn tick(&mut self) -> FrameContext {
+        let now = Instant::now();
+        let context = FrameContext {
+            total: now - self.start,
+            delta: now - self.last,
+            frame: self.frame,
+        };
+        self.last = now;
+        self.frame += 1;
+        context
+    }
+
+    /// Frames ticked so far.
+    pub fn frames(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+impl Default for FrameClock {
+    fn default() -> Self {
+        FrameClock::new()
+    }
+}
+
+/// Sleeps away the rest of each frame to hold a target frame rate.
+///
+/// Frames are paced against a running deadline rather than measured one by
+/// one, so time lost to sleep overshoot is made up on the next frame. A frame
+/// that runs past its deadline is not compensated for later; the schedule
+/// restarts from it instead of rushing the following frames.
+#[derive(Clone, Copy, Debug)]
+pub struct FrameLimiter {
+    period: Option<Duration>,
+    deadline: Option<Instant>,
+}
+
+impl FrameLimiter {
+    /// `None` leaves the frame rate uncapped, as does a rate too small or
+    /// invalid to give a representable frame period.
+    pub fn new(target_fps: Option<f32>) -> Self {
+        FrameLimiter {
+            period: target_fps
+                .filter(|&fps| fps > 0.0)
+                .and_then(|fps| Duration::try_from_secs_f32(1.0 / fps).ok()),
+            deadline: None,
+        }
+    }
+
+    pub fn period(&self) -> Option<Duration> {
+        self.period
+    }
+
+    /// Call at the end of each frame; returns how long it slept.
+    pub fn wait(&mut self) -> Duration {
+        let period = match self.period {
+            Some(period) => period,
+            None => return Duration::from_secs(0),
+        };
+        let now = Instant::now();
+        let deadline = match self.deadline {
+            Some(deadline) if deadline > now => deadline,
+            _ => {
+                self.deadline = Some(now + period);
+                return Duration::from_secs(0);
+            }
+        };
+        let remaining = deadline - now;
+        thread::sleep(remaining);
+        self.deadline = Some(deadline + period);
+        remaining
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_rates_leave_the_frame_rate_uncapped() {
+        for fps in [None, Some(0.0), Some(-60.0), Some(f32::NAN), Some(1e-45)] {
+            let mut limiter = FrameLimiter::new(fps);
+            assert_eq!(limiter.period(), None);
+            assert_eq!(limiter.wait(), Duration::ZERO);
+        }
+        assert_eq!(
+            FrameLimiter::new(Some(50.0)).period(),
+            Some(Duration::from_millis(20))
+        );
+    }
+
+    #[test]
+    fn overrun_restarts_the_schedule() {
+        let mut limiter = FrameLimiter::new(Some(50.0));
+        let period = limiter.period().unwrap();
+        assert_eq!(limiter.wait(), Duration::ZERO);
+
+        // After a long frame the next one gets a full period again instead
+        // of being rushed to catch up.
+        thread::sleep(period * 3);
+        assert_eq!(limiter.wait(), Duration::ZERO);
+        let slept = limiter.wait();
+        assert!(slept > Duration::ZERO && slept <= period);
+    }
+}
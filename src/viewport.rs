@@ -0,0 +1,94 @@
+//! Splitting one window into several viewports, each with its own camera.
+
+/// A pixel rectangle within the window, origin at the top left.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub fn aspect(&self) -> f32 {
+        self.width as f32 / self.height.max(1) as f32
+    }
+
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x as f32
+            && y >= self.y as f32
+            && x < (self.x + self.width) as f32
+            && y < (self.y + self.height) as f32
+    }
+
+    /// Maps a window position inside the rectangle to normalized device
+    /// coordinates of that viewport, with +y up.
+    pub fn to_ndc(&self, x: f32, y: f32) -> (f32, f32) {
+        let u = (x - self.x as f32) / self.width.max(1) as f32;
+        let v = (y - self.y as f32) / self.height.max(1) as f32;
+        (u * 2.0 - 1.0, 1.0 - v * 2.0)
+    }
+}
+
+/// The camera each pane of an editor layout looks through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum View {
+    Perspective,
+    Top,
+    Front,
+    Side,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layout {
+    Single,
+    /// Two panes next to each other.
+    SideBySide,
+    /// Perspective, top, front and side views in a 2x2 grid.
+    Quad,
+}
+
+impl Layout {
+    /// The panes of this layout for a window of the given size, in draw order.
+    ///
+    /// Pane edges are rounded so the panes tile the window exactly, even when
+    /// the size does not divide evenly.
+    pub fn panes(self, width: u32, height: u32) -> Vec<(View, Rect)> {
+        let (columns, rows, views): (u32, u32, &[View]) = match self {
+            Layout::Single => (1, 1, &[View::Perspective]),
+            Layout::SideBySide => (2, 1, &[View::Perspective, View::Top]),
+            Layout::Quad => (
+                2,
+                2,
+                &[View::Perspective, View::Top, View::Front, View::Side],
+            ),
+        };
+        let split = |size: u32, parts: u32, i: u32| size * i / parts;
+        views
+            .iter()
+            .enumerate()
+            .map(|(i, &view)| {
+                let (column, row) = (i as u32 % columns, i as u32 / columns);
+                let x = split(width, columns, column);
+                let y = split(height, rows, row);
+                let rect = Rect {
+                    x,
+                    y,
+                    width: split(width, columns, column + 1) - x,
+                    height: split(height, rows, row + 1) - y,
+                };
+                (view, rect)
+            })
+            .collect()
+    }
+}
+
+/// Finds the pane under a window-space cursor position and returns its index
+/// with the cursor in that pane's NDC, ready to build a picking ray using
+/// the pane's own camera.
+pub fn pane_at(panes: &[(View, Rect)], x: f32, y: f32) -> Option<(usize, (f32, f32))> {
+    panes
+        .iter()
+        .position(|(_, rect)| rect.contains(x, y))
+        .map(|index| (index, panes[index].1.to_ndc(x, y)))
+}
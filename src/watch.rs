@@ -0,0 +1,58 @@
+//! Polling file watcher used by the hot-reload paths.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Detects modifications to a file by polling its mtime.
+///
+/// A change is only reported once the file has gone `debounce` without
+/// further modification, so an editor that writes a file in several steps
+/// triggers a single reload.
+#[derive(Debug)]
+pub struct FileWatcher {
+    path: PathBuf,
+    debounce: Duration,
+    modified: Option<SystemTime>,
+    pending: Option<Instant>,
+}
+
+impl FileWatcher {
+    pub fn new<P: AsRef<Path>>(path: P, debounce: Duration) -> Self {
+        let path = path.as_ref().to_owned();
+        let modified = modified_time(&path);
+        FileWatcher {
+            path,
+            debounce,
+            modified,
+            pending: None,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns `true` once per settled modification.
+    pub fn poll(&mut self) -> bool {
+        let now = Instant::now();
+        let modified = modified_time(&self.path);
+        if modified != self.modified {
+            self.modified = modified;
+            self.pending = Some(now);
+            return false;
+        }
+        match self.pending {
+            Some(since) if now.duration_since(since) >= self.debounce => {
+                self.pending = None;
+                // A deleted file is not worth reloading; wait for it to return.
+                modified.is_some()
+            }
+            _ => false,
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}